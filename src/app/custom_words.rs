@@ -0,0 +1,145 @@
+//! Words missing from the dictionary, taught by the user with their stress.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use egui::{RichText, TextEdit};
+use quickpoeter::{api::string2word, finder::WordCollector};
+
+use super::{i18n::tr, prosody::is_vowel};
+
+/// Word → index of its stressed syllable.
+pub type CustomWords = BTreeMap<String, usize>;
+
+/// The user's words to append to the dictionary when it's loaded.
+static MERGED: Mutex<CustomWords> = Mutex::new(BTreeMap::new());
+
+/// Size of the dictionary before [`merged`] appended anything.
+static BUNDLED: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets the words [`merged`] appends; to be called before the dictionary is loaded.
+pub fn merge_on_load(words: &CustomWords) {
+    if let Ok(mut merged) = MERGED.lock() {
+        *merged = words.clone();
+    }
+}
+
+/// The words the loaded dictionary has been given.
+pub fn merged_words() -> CustomWords {
+    MERGED.lock().map(|m| m.clone()).unwrap_or_default()
+}
+
+/// The dictionary with the user's words appended, so that they are found as
+/// rhymes too. The core analyzes them from the spelling of [`mark_stress`].
+pub fn merged(mut collector: WordCollector) -> WordCollector {
+    BUNDLED.store(collector.words.len(), Ordering::Relaxed);
+    for (word, stress) in merged_words() {
+        if collector.get_word(&word).is_some() {
+            continue;
+        }
+        match string2word(&collector, &mark_stress(&word, stress)) {
+            Ok(mut analyzed) => {
+                analyzed.src = word;
+                collector.words.push(analyzed);
+            }
+            Err(e) => log::warn!("can't add the word {word}: {e}"),
+        }
+    }
+    collector
+}
+
+/// How many words the dictionary had without the user's ones, known once [`merged`] ran.
+pub fn bundled_count() -> usize {
+    BUNDLED.load(Ordering::Relaxed)
+}
+
+/// Spelling understood by the core for words outside the dictionary:
+/// an apostrophe right after the stressed vowel.
+pub fn mark_stress(word: &str, stress: usize) -> String {
+    let mut marked = String::with_capacity(word.len() + 1);
+    let mut syllable = 0;
+    for c in word.chars() {
+        marked.push(c);
+        if is_vowel(c) {
+            if syllable == stress {
+                marked.push('\'');
+            }
+            syllable += 1;
+        }
+    }
+    marked
+}
+
+/// The word and its stress from the spelling of [`mark_stress`], `None` if
+/// the stress isn't marked or isn't right after a vowel.
+pub fn parse_marked(marked: &str) -> Option<(String, usize)> {
+    let (before, after) = marked.trim().split_once('\'')?;
+    if !before.ends_with(is_vowel) {
+        return None;
+    }
+    let stress = before.chars().filter(|&c| is_vowel(c)).count() - 1;
+    Some((format!("{before}{after}").to_lowercase(), stress))
+}
+
+/// The "new word" dialog.
+pub struct NewWord {
+    pub word: String,
+    pub stress: usize,
+}
+
+impl NewWord {
+    pub fn new(word: &str) -> Self {
+        Self {
+            word: word.trim().to_lowercase(),
+            stress: 0,
+        }
+    }
+
+    /// Shows the dialog. Returns `Some(true)` when the word is confirmed,
+    /// `Some(false)` when cancelled.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<bool> {
+        let mut done = None;
+
+        egui::Window::new(tr("Новое слово"))
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.add(TextEdit::singleline(&mut self.word).hint_text(tr("Слово")));
+
+                ui.label(tr("Ударный слог:"));
+                let mut syllable = 0;
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing.x = 0.0;
+                    for c in self.word.chars() {
+                        if is_vowel(c) {
+                            let text = RichText::new(c.to_string()).size(20.0);
+                            if ui.selectable_label(self.stress == syllable, text).clicked() {
+                                self.stress = syllable;
+                            }
+                            syllable += 1;
+                        } else {
+                            ui.label(RichText::new(c.to_string()).size(20.0));
+                        }
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(self.stress < syllable, egui::Button::new(tr("Добавить")))
+                        .clicked()
+                    {
+                        done = Some(true);
+                    }
+                    if ui.button(tr("Отмена")).clicked() {
+                        done = Some(false);
+                    }
+                });
+            });
+
+        done
+    }
+}
@@ -0,0 +1,231 @@
+use std::{collections::VecDeque, num::NonZeroUsize};
+
+use clru::CLruCache;
+use egui::text::{CCursor, CCursorRange};
+use egui::text_edit::TextEditState;
+
+use super::{highlighter::words, prosody::syllable_count};
+
+/// How many snapshots of the text can be undone.
+const HISTORY_LEN: usize = 100;
+
+/// Seconds without typing after which the edit gets its own snapshot.
+const SNAPSHOT_PAUSE: f64 = 1.0;
+
+/// How many lines the counter remembers.
+const COUNTED_LINES: usize = 1024;
+
+/// What pressing Enter in the main text does.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Default)]
+pub enum EnterBehavior {
+    /// Enter always inserts a newline.
+    #[default]
+    Newline,
+    /// Enter searches rhymes for the current line, Shift+Enter inserts a newline.
+    SearchOnEnter,
+    /// Shift+Enter searches rhymes for the current line, Enter inserts a newline.
+    SearchOnShiftEnter,
+}
+
+impl EnterBehavior {
+    pub const ALL: [Self; 3] = [Self::Newline, Self::SearchOnEnter, Self::SearchOnShiftEnter];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Newline => "Enter — новая строка",
+            Self::SearchOnEnter => "Enter — рифма к строке, Shift+Enter — новая строка",
+            Self::SearchOnShiftEnter => "Shift+Enter — рифма к строке",
+        }
+    }
+
+    /// Modifiers that make Enter search instead of inserting a newline.
+    pub fn search_modifiers(self) -> Option<egui::Modifiers> {
+        match self {
+            Self::Newline => None,
+            Self::SearchOnEnter => Some(egui::Modifiers::NONE),
+            Self::SearchOnShiftEnter => Some(egui::Modifiers::SHIFT),
+        }
+    }
+}
+
+/// Last word of the line containing the char with index `char_index`.
+pub fn line_end_word(text: &str, char_index: usize) -> Option<&str> {
+    let byte_index = text
+        .char_indices()
+        .nth(char_index)
+        .map_or(text.len(), |(i, _)| i);
+    let start = text[..byte_index].rfind('\n').map_or(0, |i| i + 1);
+    let end = text[byte_index..]
+        .find('\n')
+        .map_or(text.len(), |i| byte_index + i);
+
+    last_word(&text[start..end])
+}
+
+/// Last word of the line, hyphenated ones kept whole.
+pub fn last_word(line: &str) -> Option<&str> {
+    line.split(|c: char| !c.is_alphabetic() && c != '-')
+        .rfind(|w| w.chars().any(char::is_alphabetic))
+}
+
+pub fn main_text_id() -> egui::Id {
+    egui::Id::new("main_text")
+}
+
+/// Char index of the caret in the editor with `id`.
+pub fn cursor(ctx: &egui::Context, id: egui::Id) -> Option<usize> {
+    TextEditState::load(ctx, id)
+        .and_then(|state| state.ccursor_range())
+        .map(|range| range.primary.index)
+}
+
+/// Index of the line containing the char with index `char_index`.
+pub fn line_of(text: &str, char_index: usize) -> usize {
+    text.chars().take(char_index).filter(|&c| c == '\n').count()
+}
+
+/// Puts `word` at the caret of the editor with `id`, replacing the selection,
+/// and adds spaces so that it doesn't stick to the neighbouring words.
+pub fn insert_at_cursor(ctx: &egui::Context, id: egui::Id, text: &mut String, word: &str) {
+    let mut state = TextEditState::load(ctx, id).unwrap_or_default();
+    let char_count = text.chars().count();
+    let (start, end) = state
+        .ccursor_range()
+        .map_or((char_count, char_count), |range| {
+            let (a, b) = (range.primary.index, range.secondary.index);
+            (a.min(b).min(char_count), a.max(b).min(char_count))
+        });
+
+    let byte = |char_index| {
+        text.char_indices()
+            .nth(char_index)
+            .map_or(text.len(), |(i, _)| i)
+    };
+    let (start_byte, end_byte) = (byte(start), byte(end));
+
+    let needs_space = |c: Option<char>| c.is_some_and(|c| !c.is_whitespace());
+    let mut insertion = String::new();
+    if needs_space(text[..start_byte].chars().next_back()) {
+        insertion.push(' ');
+    }
+    insertion.push_str(word);
+    if needs_space(text[end_byte..].chars().next()) {
+        insertion.push(' ');
+    }
+
+    text.replace_range(start_byte..end_byte, &insertion);
+
+    let cursor = CCursor::new(start + insertion.chars().count());
+    state.set_ccursor_range(Some(CCursorRange::one(cursor)));
+    state.store(ctx, id);
+    ctx.memory_mut(|m| m.request_focus(id));
+}
+
+/// Snapshots of the main text for undo and redo, taken on new lines and pauses in typing.
+#[derive(Default)]
+pub struct History {
+    undo: VecDeque<String>,
+    redo: Vec<String>,
+    /// The text as of the latest snapshot.
+    last: Option<String>,
+    /// When the text started to differ from `last`.
+    edited_at: Option<f64>,
+}
+
+impl History {
+    /// Called every frame with the current text.
+    pub fn track(&mut self, text: &str, now: f64) {
+        let Some(last) = &self.last else {
+            self.last = Some(text.to_string());
+            return;
+        };
+        if last == text {
+            self.edited_at = None;
+            return;
+        }
+
+        let edited_at = *self.edited_at.get_or_insert(now);
+        let new_line = text.matches('\n').count() != last.matches('\n').count();
+        if new_line || now - edited_at >= SNAPSHOT_PAUSE {
+            self.snapshot(text);
+        }
+    }
+
+    fn snapshot(&mut self, text: &str) {
+        if let Some(last) = self.last.replace(text.to_string()) {
+            if last != text {
+                self.undo.push_back(last);
+                if self.undo.len() > HISTORY_LEN {
+                    self.undo.pop_front();
+                }
+                self.redo.clear();
+            }
+        }
+        self.edited_at = None;
+    }
+
+    pub fn undo(&mut self, text: &mut String) {
+        // edits not snapshotted yet are undone first
+        self.snapshot(text);
+        if let Some(previous) = self.undo.pop_back() {
+            self.redo.push(std::mem::replace(text, previous.clone()));
+            self.last = Some(previous);
+        }
+    }
+
+    pub fn redo(&mut self, text: &mut String) {
+        if let Some(next) = self.redo.pop() {
+            self.undo.push_back(std::mem::replace(text, next.clone()));
+            self.last = Some(next);
+        }
+    }
+}
+
+/// Sizes of the text shown in the status bar.
+#[derive(Default, Clone, Copy)]
+pub struct Counts {
+    pub chars: usize,
+    pub words: usize,
+    pub lines: usize,
+    pub syllables: usize,
+}
+
+/// Counts the text line by line, remembering the lines it has seen, so that
+/// typing recounts only the edited one.
+pub struct Counter {
+    lines: CLruCache<String, Counts>,
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Self {
+            lines: CLruCache::new(NonZeroUsize::new(COUNTED_LINES).expect("non-zero")),
+        }
+    }
+}
+
+impl Counter {
+    pub fn count(&mut self, text: &str) -> Counts {
+        let mut total = Counts::default();
+        for line in text.split_inclusive('\n') {
+            let counts = match self.lines.get(line) {
+                Some(&counts) => counts,
+                None => {
+                    let counts = Counts {
+                        chars: line.chars().count(),
+                        words: words(line).len(),
+                        lines: 1,
+                        syllables: syllable_count(line),
+                    };
+                    self.lines.put(line.to_string(), counts);
+                    counts
+                }
+            };
+            total.chars += counts.chars;
+            total.words += counts.words;
+            total.lines += counts.lines;
+            total.syllables += counts.syllables;
+        }
+        total
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+
+use super::i18n::tr;
+
+/// Why a search found nothing.
+#[derive(Clone, PartialEq)]
+pub enum SearchError {
+    QueryTooLong(usize),
+    /// The query has no Russian letters, typed in another layout or language.
+    NotCyrillic,
+    /// The dictionary doesn't know the word to rhyme.
+    UnknownQueryWord(String),
+    EmptyTheme,
+    UnknownThemeWords(Vec<String>),
+    /// Reported by the core, e.g. the query word can't be analyzed.
+    Core(String),
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::QueryTooLong(max) => {
+                write!(
+                    f,
+                    "{}: {max}",
+                    tr("Слишком длинный запрос, букв больше чем")
+                )
+            }
+            Self::NotCyrillic => write!(f, "{}", tr("Введите слово кириллицей")),
+            Self::UnknownQueryWord(word) => write!(f, "{}: {word}", tr("Слова нет в словаре")),
+            Self::EmptyTheme => write!(f, "{}", tr("Тема пуста, введите хотя бы одно слово")),
+            Self::UnknownThemeWords(words) => {
+                let label = match words.len() {
+                    1 => tr("Неизвестное слово в теме"),
+                    _ => tr("Неизвестные слова в теме"),
+                };
+                write!(f, "{label}: {}", words.join(", "))
+            }
+            Self::Core(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SearchError;
+
+    #[test]
+    fn empty_theme() {
+        assert_eq!(
+            SearchError::EmptyTheme.to_string(),
+            "Тема пуста, введите хотя бы одно слово"
+        );
+    }
+
+    #[test]
+    fn unknown_theme_words() {
+        let one = SearchError::UnknownThemeWords(vec!["глокая".into()]);
+        assert_eq!(one.to_string(), "Неизвестное слово в теме: глокая");
+
+        let several = SearchError::UnknownThemeWords(vec!["глокая".into(), "куздра".into()]);
+        assert_eq!(
+            several.to_string(),
+            "Неизвестные слова в теме: глокая, куздра"
+        );
+    }
+
+    #[test]
+    fn unknown_query_word() {
+        let error = SearchError::UnknownQueryWord("бокр".into());
+        assert_eq!(error.to_string(), "Слова нет в словаре: бокр");
+    }
+}
@@ -0,0 +1,187 @@
+//! Saving text to a file: through a dialog on desktop, as a download on the web.
+//! And opening one back.
+
+use poll_promise::Promise;
+
+use super::i18n::tr;
+
+/// How a list of results is written out.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ResultStyle {
+    /// A word per line.
+    #[default]
+    Plain,
+    /// `1. word` per line.
+    Numbered,
+    /// All on one line, separated by commas.
+    Commas,
+}
+
+impl ResultStyle {
+    pub const ALL: [Self; 3] = [Self::Plain, Self::Numbered, Self::Commas];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Plain => "По слову в строке",
+            Self::Numbered => "Нумерованный список",
+            Self::Commas => "Через запятую",
+        }
+    }
+}
+
+/// The words as text in the style, to copy or save.
+pub fn format_results(words: &[&str], style: ResultStyle) -> String {
+    match style {
+        ResultStyle::Plain => words.join("\n"),
+        ResultStyle::Numbered => words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| format!("{}. {w}", i + 1))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        ResultStyle::Commas => words.join(", "),
+    }
+}
+
+/// Letters of a rhyme scheme, as Russian schemes are written.
+const SCHEME_LETTERS: &str = "АБВГДЕЖЗИКЛМНОПРСТУФХЦЧШЩЭЮЯ";
+
+/// A line of the poem with what the analysis found in it.
+pub struct AnnotatedLine<'a> {
+    pub text: &'a str,
+    /// Index of the letter of the rhyme scheme.
+    pub rhyme: Option<usize>,
+    /// The rhyme goes against the expected scheme.
+    pub mismatch: bool,
+    pub stresses: String,
+}
+
+/// The poem as a Markdown table of lines, their rhymes and stresses.
+pub fn poem_report(
+    lines: &[AnnotatedLine<'_>],
+    meter: Option<&str>,
+    expected_scheme: Option<&str>,
+) -> String {
+    let mut report = String::new();
+    if let Some(meter) = meter {
+        report += &format!("{}: {meter}\n\n", tr("Размер"));
+    }
+    if let Some(scheme) = expected_scheme {
+        report += &format!("{}: {scheme}\n\n", tr("Схема рифмовки"));
+    }
+    report += &format!(
+        "| {} | {} | {} |\n|---|---|---|\n",
+        tr("Строка"),
+        tr("Рифма"),
+        tr("Ударения")
+    );
+    for line in lines {
+        let mut letter = line.rhyme.map_or(String::new(), |i| {
+            SCHEME_LETTERS
+                .chars()
+                .nth(i)
+                .map_or((i + 1).to_string(), String::from)
+        });
+        if line.mismatch {
+            letter += " ⚠";
+        }
+        report += &format!(
+            "| {} | {letter} | `{}` |\n",
+            line.text.replace('|', "\\|"),
+            line.stresses
+        );
+    }
+    report
+}
+
+/// Extension of the file name, to filter the dialog by.
+fn extension(file_name: &str) -> &str {
+    file_name.rsplit_once('.').map_or("txt", |(_, ext)| ext)
+}
+
+/// Saves `contents` where the user chooses; cancelling the dialog isn't an error.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_text(file_name: &str, contents: &str) -> Result<(), String> {
+    let Some(path) = rfd::FileDialog::new()
+        .set_file_name(file_name)
+        .add_filter(tr("Файлы"), &[extension(file_name)])
+        .save_file()
+    else {
+        return Ok(());
+    };
+    std::fs::write(&path, contents).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save_text(file_name: &str, contents: &str) -> Result<(), String> {
+    use wasm_bindgen::JsCast;
+
+    let unavailable = || tr("Браузер не дал скачать файл").to_string();
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or_else(unavailable)?;
+    let anchor = document.create_element("a").map_err(|_| unavailable())?;
+    let href = format!("data:text/plain;charset=utf-8,{}", percent_encode(contents));
+    anchor
+        .set_attribute("href", &href)
+        .and_then(|_| anchor.set_attribute("download", file_name))
+        .map_err(|_| unavailable())?;
+    let anchor = anchor
+        .dyn_into::<web_sys::HtmlElement>()
+        .map_err(|_| unavailable())?;
+    anchor.click();
+    Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len() * 3);
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Lets the user pick a file with the extension; the promise yields its contents,
+/// `None` if the dialog was cancelled or the file couldn't be read.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open_text(extension: &str) -> Promise<Option<String>> {
+    let contents = rfd::FileDialog::new()
+        .add_filter(tr("Файлы"), &[extension])
+        .pick_file()
+        .and_then(|path| match std::fs::read_to_string(&path) {
+            Ok(contents) => Some(contents),
+            Err(e) => {
+                log::warn!("can't read {}: {e}", path.display());
+                None
+            }
+        });
+    Promise::from_ready(contents)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn open_text(extension: &str) -> Promise<Option<String>> {
+    let dialog = rfd::AsyncFileDialog::new().add_filter(tr("Файлы"), &[extension]);
+    Promise::spawn_local(async move {
+        let file = dialog.pick_file().await?;
+        String::from_utf8(file.read().await).ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_results, ResultStyle};
+
+    #[test]
+    fn result_styles() {
+        let words = ["а", "б"];
+        assert_eq!(format_results(&words, ResultStyle::Plain), "а\nб");
+        assert_eq!(format_results(&words, ResultStyle::Numbered), "1. а\n2. б");
+        assert_eq!(format_results(&words, ResultStyle::Commas), "а, б");
+    }
+}
@@ -0,0 +1,38 @@
+//! Explanations of the rhyme settings, shown on hovering their sliders.
+
+/// What the slider with the label does and which way is stricter.
+pub fn slider_help(label: &str) -> Option<&'static str> {
+    Some(match label {
+        "Тематика" => "Насколько важна близость рифмы к теме по смыслу; больше — ближе к теме",
+        "Популярность слова" => "Насколько выше поднимаются частые слова; больше — реже редкие слова",
+        "Ударения" => "Вес совпадения гласных и места ударения; больше — точнее созвучие гласных",
+        "Структура" => "Вес совпадения согласных между гласными; больше — строже к согласным",
+        "Аллитерации" => "Вес похожих согласных в концовке; больше — строже к согласным концовки",
+        "Вес" => "Насколько эта часть влияет на итоговую оценку; 0 — не влияет совсем",
+        "Степень" => "Степень, в которую возводится расхождение; больше — сильнее наказываются большие расхождения",
+        "Степень для одного слова" => "То же для темы из одного слова",
+        "Множитель для одного слова" => "Во сколько раз иначе учитывается тема из одного слова; больше — сильнее",
+        "Близкая длина" => "Штраф за разницу в длине слов; больше — ближе по длине",
+        "Совпадающие гласные/согласные в конце" => "Поощрение одинакового последнего звука; больше — важнее одинаковый конец",
+        "Строгие ударения" => "Штраф за несовпадение ударных гласных; больше — строже",
+        "Нестрогие ударения" => "Штраф за несовпадение безударных гласных; больше — строже",
+        "Штраф за плохой ритм" => "Штраф за ударение на другом слоге от конца; больше — строже к ритму",
+        "Сдвиг веса сравнения гласных" => "Насколько значимы гласные дальше от конца; больше — вес всех гласных ровнее",
+        "Степень веса сравнения гласных" => "Как быстро падает важность гласных к началу слова; больше — важна только концовка",
+        "Ассимптотика метрики" => "Как быстро штраф выходит на предел; больше — большие расхождения наказываются сильнее",
+        "Сдвиг ассимптотики метрики" | "Сдвиг ассимптотики" => "С какого расхождения штраф начинает выходить на предел; больше — мелкие различия прощаются",
+        "Идеальная длина" => "Длина рифмы в буквах, за которую нет штрафа",
+        "Вес штрафа меньших" => "Штраф за рифмы короче идеальной; больше — короткие реже",
+        "Степень штрафа меньших" => "Как быстро растёт штраф за короткие рифмы; больше — быстрее",
+        "Вес штрафа больших" => "Штраф за рифмы длиннее идеальной; больше — длинные реже",
+        "Степень штрафа больших" => "Как быстро растёт штраф за длинные рифмы; больше — быстрее",
+        "Глаголы" | "Прилагательные" | "Существительные" | "Наречия" => "Штраф, если рифма той же части речи, что и запрос; больше — разнообразнее рифмы",
+        "Степень разности длин слогов" => "Как сильно наказывается разное число согласных между гласными; больше — строже",
+        "Сдвиг множителя сравнения с конца" | "Сдвиг важности согласных в концовке" => "Насколько значимы согласные дальше от конца; больше — вес всех согласных ровнее",
+        "Степень множителя сравнения с конца" | "Степень важности согласных в концовке" => "Как быстро падает важность согласных к началу слова; больше — важна только концовка",
+        "Сдвиг расстояния в слове между буквами" => "Насколько прощается сдвиг похожих согласных внутри слова; больше — мягче",
+        "Степень расстояния в слове между буквами" => "Как быстро растёт штраф за сдвиг согласных; больше — строже к их порядку",
+        "Штраф за дополнительные звуки" => "Штраф за согласные, которых нет в другом слове; больше — строже",
+        _ => return None,
+    })
+}
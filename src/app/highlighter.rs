@@ -1,13 +1,436 @@
+use std::{collections::HashMap, num::NonZeroUsize, ops::Range};
+
 use clru::CLruCache;
-use egui::RichText;
+use egui::{
+    text::{LayoutJob, TextFormat},
+    Color32, FontId, Stroke,
+};
+
+use super::{
+    homophones::phonetic_key,
+    prosody::{is_vowel, stressed_ending, syllable_count},
+    WORD_COLLECTOR,
+};
+
+const CACHE_SIZE: usize = 256;
 
-enum HighlightMode {
+/// Colors of highlighted sounds; the same sound always gets the same color.
+const PALETTE: [Color32; 6] = [
+    Color32::from_rgb(220, 80, 80),
+    Color32::from_rgb(80, 150, 220),
+    Color32::from_rgb(90, 180, 90),
+    Color32::from_rgb(210, 150, 40),
+    Color32::from_rgb(170, 100, 210),
+    Color32::from_rgb(40, 170, 170),
+];
+
+const STRESSED: Color32 = Color32::from_rgb(220, 80, 80);
+const UNSTRESSED: Color32 = Color32::from_rgb(120, 120, 120);
+const UNKNOWN: Color32 = Color32::from_rgb(220, 60, 60);
+/// Last words of the lines rhyming against the expected scheme.
+const MISMATCH: Color32 = Color32::from_rgb(230, 40, 40);
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Default)]
+pub enum HighlightMode {
+    /// Stressed and unstressed vowels of the words the dictionary knows.
     Rythm,
+    /// Consonants repeated in nearby words.
+    Alliteration,
+    /// Words missing from the dictionary.
+    Unknown,
+    /// Rhyming line endings, a background color per rhyme.
+    Scheme,
+    #[default]
     No,
 }
 
-struct Highlighter {
-    cache_highlight: CLruCache<String, RichText>,
-    cache_words: CLruCache<String, RichText>,
+impl HighlightMode {
+    pub const ALL: [Self; 5] = [
+        Self::No,
+        Self::Rythm,
+        Self::Alliteration,
+        Self::Unknown,
+        Self::Scheme,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rythm => "Ритм",
+            Self::Alliteration => "Аллитерации",
+            Self::Unknown => "Неизвестные слова",
+            Self::Scheme => "Схема рифмовки",
+            Self::No => "Без подсветки",
+        }
+    }
+}
+
+/// What the highlighter needs to know about a word.
+#[derive(Clone)]
+struct WordInfo {
+    /// Consonant letters with their byte offsets in the word.
+    consonants: Vec<(usize, char)>,
+    /// The dictionary has the word.
+    known: bool,
+    /// How the stressed ending sounds, if the stress is known.
+    ending: Option<Vec<char>>,
+    /// Index of the stressed syllable.
+    stress: Option<usize>,
+}
+
+/// Part of the text to paint with the color, the background one in the rhyme scheme.
+type Span = (Range<usize>, Color32);
+
+pub struct Highlighter {
+    /// Text (a single line for the rythm and unknown words) → its highlighted spans.
+    cache_highlight: CLruCache<String, Vec<Span>>,
+    cache_words: CLruCache<String, WordInfo>,
+    /// Line → its stress pattern.
+    cache_patterns: CLruCache<String, String>,
     mode: HighlightMode,
+    /// How many words apart repeated consonants are still highlighted.
+    alliteration_radius: usize,
+    /// Scheme the poem is meant to follow, a letter per non-empty line.
+    expected_scheme: Option<Vec<char>>,
+}
+
+impl Default for Highlighter {
+    fn default() -> Self {
+        let size = NonZeroUsize::new(CACHE_SIZE).expect("non-zero");
+        Self {
+            cache_highlight: CLruCache::new(size),
+            cache_words: CLruCache::new(size),
+            cache_patterns: CLruCache::new(size),
+            mode: HighlightMode::No,
+            alliteration_radius: 2,
+            expected_scheme: None,
+        }
+    }
+}
+
+impl Highlighter {
+    pub fn set_mode(&mut self, mode: HighlightMode) {
+        if self.mode != mode {
+            self.mode = mode;
+            self.cache_highlight.clear();
+        }
+    }
+
+    pub fn set_alliteration_radius(&mut self, radius: usize) {
+        if self.alliteration_radius != radius {
+            self.alliteration_radius = radius;
+            if self.mode == HighlightMode::Alliteration {
+                self.cache_highlight.clear();
+            }
+        }
+    }
+
+    pub fn set_expected_scheme(&mut self, scheme: Option<&str>) {
+        let scheme = scheme.map(|s| s.chars().collect());
+        if self.expected_scheme != scheme {
+            self.expected_scheme = scheme;
+            if self.mode == HighlightMode::Scheme {
+                self.cache_highlight.clear();
+            }
+        }
+    }
+
+    pub fn highlight(&mut self, text: &str, font_id: FontId, color: Color32) -> LayoutJob {
+        let spans = match self.mode {
+            HighlightMode::Alliteration => self.cached(text, Self::alliteration),
+            HighlightMode::Rythm => self.by_lines(text, |_, line| rythm(line)),
+            HighlightMode::Unknown => self.by_lines(text, Self::unknown),
+            HighlightMode::Scheme => self.cached(text, Self::scheme),
+            HighlightMode::No => vec![],
+        };
+
+        let plain = TextFormat::simple(font_id.clone(), color);
+        let highlighted = |span_color| match self.mode {
+            HighlightMode::Scheme if span_color == MISMATCH => TextFormat {
+                color: MISMATCH,
+                underline: Stroke::new(1.0, MISMATCH),
+                ..plain.clone()
+            },
+            HighlightMode::Scheme => TextFormat {
+                background: span_color,
+                ..plain.clone()
+            },
+            _ => TextFormat::simple(font_id.clone(), span_color),
+        };
+        let mut job = LayoutJob::default();
+        let mut end = 0;
+        for (range, span_color) in spans {
+            job.append(&text[end..range.start], 0.0, plain.clone());
+            job.append(&text[range.clone()], 0.0, highlighted(span_color));
+            end = range.end;
+        }
+        job.append(&text[end..], 0.0, plain);
+        job
+    }
+
+    fn cached(
+        &mut self,
+        text: &str,
+        compute: impl FnOnce(&mut Self, &str) -> Vec<Span>,
+    ) -> Vec<Span> {
+        if let Some(spans) = self.cache_highlight.get(text) {
+            return spans.clone();
+        }
+        let spans = compute(self, text);
+        self.cache_highlight.put(text.to_string(), spans.clone());
+        spans
+    }
+
+    /// Lines are independent, so editing one doesn't recompute the rest.
+    fn by_lines(&mut self, text: &str, compute: fn(&mut Self, &str) -> Vec<Span>) -> Vec<Span> {
+        let mut spans = vec![];
+        let mut start = 0;
+        for line in text.split_inclusive('\n') {
+            spans.extend(
+                self.cached(line, compute)
+                    .into_iter()
+                    .map(|(range, color)| (range.start + start..range.end + start, color)),
+            );
+            start += line.len();
+        }
+        spans
+    }
+
+    fn word_info(&mut self, word: &str) -> WordInfo {
+        if let Some(info) = self.cache_words.get(word) {
+            return info.clone();
+        }
+
+        let known = WORD_COLLECTOR.get_word(&word.to_lowercase());
+        let info = WordInfo {
+            consonants: word
+                .char_indices()
+                .filter(|&(_, c)| c.is_alphabetic() && !is_vowel(c) && !"ьъйЬЪЙ".contains(c))
+                .collect(),
+            known: known.is_some(),
+            ending: stressed_ending(&WORD_COLLECTOR, word).map(phonetic_key),
+            stress: known.map(|w| w.stress),
+        };
+        self.cache_words.put(word.to_string(), info.clone());
+        info
+    }
+
+    /// Syllables of the line, a word per group: `1` for stressed, `0` for
+    /// unstressed and `?` where the stress is unknown or free, as in one-syllable words.
+    pub fn stress_pattern(&mut self, line: &str) -> String {
+        if let Some(pattern) = self.cache_patterns.get(line) {
+            return pattern.clone();
+        }
+
+        let mut groups = vec![];
+        for (_, word) in words(line) {
+            let count = syllable_count(word);
+            let group: String = match self.word_info(word).stress {
+                Some(stress) if count > 1 => (0..count)
+                    .map(|i| if i == stress { '1' } else { '0' })
+                    .collect(),
+                _ => "?".repeat(count),
+            };
+            if !group.is_empty() {
+                groups.push(group);
+            }
+        }
+        let pattern = groups.join(" ");
+        self.cache_patterns.put(line.to_string(), pattern.clone());
+        pattern
+    }
+
+    fn unknown(&mut self, line: &str) -> Vec<Span> {
+        words(line)
+            .into_iter()
+            .filter(|(_, word)| !self.word_info(word).known)
+            .map(|(start, word)| (start..start + word.len(), UNKNOWN))
+            .collect()
+    }
+
+    /// Last words of the lines that rhyme with some other line, the same rhyme the same color;
+    /// those going against the expected scheme stand out instead.
+    fn scheme(&mut self, text: &str) -> Vec<Span> {
+        let letters = self.scheme_letters(text);
+        let mismatches = match self.expected_scheme.clone() {
+            Some(expected) => self.scheme_mismatches(text, &expected),
+            None => vec![],
+        };
+        let mut spans = vec![];
+        let mut start = 0;
+        for (i, (line, letter)) in text.split_inclusive('\n').zip(letters).enumerate() {
+            if let Some(&(offset, word)) = words(line).last() {
+                let begin = start + offset;
+                let color = match letter {
+                    _ if mismatches.get(i) == Some(&true) => Some(MISMATCH),
+                    Some(letter) => Some(PALETTE[letter % PALETTE.len()].gamma_multiply(0.4)),
+                    None => None,
+                };
+                if let Some(color) = color {
+                    spans.push((begin..begin + word.len(), color));
+                }
+            }
+            start += line.len();
+        }
+        spans
+    }
+
+    /// For every line, whether its rhyme disagrees with the `expected` scheme, a
+    /// letter per non-empty line: it doesn't rhyme with a line of the same letter
+    /// or rhymes with one of another.
+    pub fn scheme_mismatches(&mut self, text: &str, expected: &[char]) -> Vec<bool> {
+        let letters = self.scheme_letters(text);
+        let mut scheme = expected.iter();
+        let expected: Vec<Option<char>> = text
+            .split_inclusive('\n')
+            .map(|line| match line.trim().is_empty() {
+                true => None,
+                false => scheme.next().copied(),
+            })
+            .collect();
+
+        let rhyme = |i: usize, j: usize| letters[i].is_some() && letters[i] == letters[j];
+        (0..expected.len())
+            .map(|i| {
+                let Some(letter) = expected[i] else {
+                    return false;
+                };
+                (0..expected.len()).any(|j| {
+                    j != i && expected[j].is_some_and(|other| (letter == other) != rhyme(i, j))
+                })
+            })
+            .collect()
+    }
+
+    /// Rhyme of every line as the index of a letter of the scheme, in order of
+    /// the first appearance; `None` for lines rhyming with no other.
+    pub fn scheme_letters(&mut self, text: &str) -> Vec<Option<usize>> {
+        let endings: Vec<_> = text
+            .split_inclusive('\n')
+            .map(|line| {
+                let &(_, word) = words(line).last()?;
+                self.word_info(word).ending
+            })
+            .collect();
+
+        let mut rhymes: Vec<&Vec<char>> = vec![];
+        endings
+            .iter()
+            .map(|ending| {
+                let ending = ending.as_ref()?;
+                let rhymed = endings
+                    .iter()
+                    .filter(|e| e.as_ref() == Some(ending))
+                    .count()
+                    > 1;
+                if !rhymed {
+                    return None;
+                }
+                Some(match rhymes.iter().position(|&r| r == ending) {
+                    Some(letter) => letter,
+                    None => {
+                        rhymes.push(ending);
+                        rhymes.len() - 1
+                    }
+                })
+            })
+            .collect()
+    }
+
+    fn alliteration(&mut self, text: &str) -> Vec<Span> {
+        let words: Vec<(usize, Vec<(usize, char)>)> = words(text)
+            .into_iter()
+            .map(|(start, word)| (start, self.word_info(word).consonants))
+            .collect();
+        let sound = |c: char| c.to_lowercase().next().unwrap_or(c);
+
+        // consonant → indices of words containing it
+        let mut occurrences: HashMap<char, Vec<usize>> = HashMap::new();
+        for (i, (_, consonants)) in words.iter().enumerate() {
+            for &(_, c) in consonants {
+                let list = occurrences.entry(sound(c)).or_default();
+                if list.last() != Some(&i) {
+                    list.push(i);
+                }
+            }
+        }
+
+        let mut colors: HashMap<char, usize> = HashMap::new();
+        let mut spans = vec![];
+        for (i, (start, consonants)) in words.iter().enumerate() {
+            for &(offset, c) in consonants {
+                let repeated = occurrences[&sound(c)]
+                    .iter()
+                    .any(|&j| j != i && j.abs_diff(i) <= self.alliteration_radius);
+                if repeated {
+                    let next_color = colors.len();
+                    let color = *colors.entry(sound(c)).or_insert(next_color);
+                    let begin = start + offset;
+                    spans.push((begin..begin + c.len_utf8(), PALETTE[color % PALETTE.len()]));
+                }
+            }
+        }
+        spans
+    }
+}
+
+/// Vowels of the line, the stressed ones standing out.
+fn rythm(line: &str) -> Vec<Span> {
+    let mut spans = vec![];
+    for (start, word) in words(line) {
+        let Some(known) = WORD_COLLECTOR.get_word(&word.to_lowercase()) else {
+            continue;
+        };
+        let vowels = word.char_indices().filter(|&(_, c)| is_vowel(c));
+        for (syllable, (offset, c)) in vowels.enumerate() {
+            let color = if syllable == known.stress {
+                STRESSED
+            } else {
+                UNSTRESSED
+            };
+            let begin = start + offset;
+            spans.push((begin..begin + c.len_utf8(), color));
+        }
+    }
+    spans
+}
+
+/// Words of the text with their byte offsets.
+pub fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words = vec![];
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_alphabetic(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                words.push((s, &text[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, &text[s..]));
+    }
+    words
+}
+
+/// Layout of custom theme words, the ones the dictionary doesn't know marked red.
+pub fn theme_words(text: &str, font_id: FontId, color: Color32) -> LayoutJob {
+    let plain = TextFormat::simple(font_id.clone(), color);
+    let unknown = TextFormat {
+        underline: Stroke::new(1.0, UNKNOWN),
+        ..TextFormat::simple(font_id, UNKNOWN)
+    };
+
+    let mut job = LayoutJob::default();
+    for piece in text.split_inclusive(char::is_whitespace) {
+        let word = piece.trim_end();
+        let format = match WORD_COLLECTOR.get_word(word) {
+            None if !word.is_empty() => unknown.clone(),
+            _ => plain.clone(),
+        };
+        job.append(word, 0.0, format);
+        job.append(&piece[word.len()..], 0.0, plain.clone());
+    }
+    job
 }
@@ -0,0 +1,83 @@
+use quickpoeter::{
+    api::{find, string2word},
+    finder::WordCollector,
+    reader::GeneralSettings,
+};
+
+/// Minimal similarity of phonetic keys for a word to count as a near-homophone.
+const THRESHOLD: f32 = 0.75;
+
+/// Finds words that sound almost like the whole `query`, not only like its ending.
+pub fn find_homophones(
+    collector: &WordCollector,
+    settings: &GeneralSettings,
+    query: &str,
+    remove_parts_of_speech: &[&str],
+    count: u32,
+) -> Result<Vec<String>, String> {
+    let mut settings = settings.clone();
+    // only the sound matters
+    settings.meaning.weight = 0.0;
+    settings.popularity.weight = 0.0;
+
+    let key = phonetic_key(query);
+    let word = string2word(collector, query)?;
+    let found = find(
+        collector,
+        &settings,
+        word,
+        None,
+        remove_parts_of_speech,
+        count,
+    )?;
+
+    Ok(found
+        .into_iter()
+        .map(|r| r.word.src.clone())
+        .filter(|w| w != query && similarity(&key, &phonetic_key(w)) >= THRESHOLD)
+        .collect())
+}
+
+/// Rough transcription: reduces vowels, devoices consonants and drops signs.
+pub fn phonetic_key(word: &str) -> Vec<char> {
+    word.to_lowercase()
+        .chars()
+        .filter_map(|c| {
+            Some(match c {
+                'о' => 'а',
+                'я' => 'а',
+                'ё' | 'э' | 'е' => 'и',
+                'ы' => 'и',
+                'ю' => 'у',
+                'б' => 'п',
+                'в' => 'ф',
+                'г' => 'к',
+                'д' => 'т',
+                'ж' => 'ш',
+                'з' => 'с',
+                'ь' | 'ъ' | '\'' | '`' => return None,
+                c => c,
+            })
+        })
+        .collect()
+}
+
+/// Normalized Levenshtein similarity from 0 (nothing common) to 1 (same).
+fn similarity(a: &[char], b: &[char]) -> f32 {
+    let len = a.len().max(b.len());
+    if len == 0 {
+        return 1.0;
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let replace = prev[j] + usize::from(ca != cb);
+            cur[j + 1] = replace.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+
+    1.0 - prev[b.len()] as f32 / len as f32
+}
@@ -0,0 +1,508 @@
+//! Translations of the interface. The Russian text itself is the key,
+//! so a missing translation just leaves it in Russian.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Language {
+    #[default]
+    Russian,
+    English,
+}
+
+impl Language {
+    pub const ALL: [Self; 2] = [Self::Russian, Self::English];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Russian => "Русский",
+            Self::English => "English",
+        }
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(Language::Russian as u8);
+
+pub fn set_language(language: Language) {
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+fn language() -> Language {
+    match CURRENT.load(Ordering::Relaxed) {
+        x if x == Language::English as u8 => Language::English,
+        _ => Language::Russian,
+    }
+}
+
+/// `text` in the current language of the interface.
+pub fn tr(text: &'static str) -> &'static str {
+    match language() {
+        Language::Russian => text,
+        Language::English => english(text).unwrap_or(text),
+    }
+}
+
+fn english(text: &str) -> Option<&'static str> {
+    Some(match text {
+        // menus
+        "Данные" => "Data",
+        "Очистить все данные…" => "Clear all data…",
+        "Очистить все данные" => "Clear all data",
+        "Будут удалены текст, заметки, пользовательские слова, пресеты, \
+        тема и все остальные сохранённые настройки. Это нельзя отменить." => {
+            "The text, notes, custom words, presets, theme and all other saved \
+            settings will be deleted. This can't be undone."
+        }
+        "Я понимаю" => "I understand",
+        "Очистить" => "Clear",
+        "Отмена" => "Cancel",
+        "Шаблоны" => "Templates",
+        "Новый шаблон…" => "New template…",
+        "Новый шаблон" => "New template",
+        "Вернуть встроенные" => "Restore built-in",
+        "Схема рифмовки" => "Rhyme scheme",
+        "Схема рифмовки, например АБАБ ВГВГ" => {
+            "Rhyme scheme, e.g. АБАБ ВГВГ"
+        }
+        "Забыть схему" => "Forget the scheme",
+        "Редактор" => "Editor",
+        "Enter — новая строка" => "Enter — new line",
+        "Enter — рифма к строке, Shift+Enter — новая строка" => {
+            "Enter — rhyme the line, Shift+Enter — new line"
+        }
+        "Shift+Enter — рифма к строке" => "Shift+Enter — rhyme the line",
+        "Рифмы к строке под курсором" => {
+            "Rhymes for the line under the caret"
+        }
+        "Без подсветки" => "No highlighting",
+        "Ритм" => "Rythm",
+        "Аллитерации" => "Alliterations",
+        "Неизвестные слова" => "Unknown words",
+        "Радиус аллитераций, слов" => "Alliteration radius, words",
+
+        // query and results
+        "Тема" => "Theme",
+        "Параметры рифм" => "Rhyme settings",
+        "Предыдущий запрос" => "Previous query",
+        "Следующий запрос" => "Next query",
+        "Закрепить запрос, чтобы его не меняли переходы по рифмам" => {
+            "Lock the query so that following rhymes doesn't change it"
+        }
+        "Идёт поиск" => "Searching",
+        "Очистить запрос" => "Clear the query",
+        "Случайное слово" => "Random word",
+        "К чему рифму?" => "Rhyme for what?",
+        "Рифма к последнему слову" => "Rhyming the last word",
+        "Как словарь понял запрос: слоги, ударный выделен; щелчок по слогу переносит ударение" => {
+            "How the dictionary understood the query: syllables, the stressed one in capitals; click a syllable to move the stress"
+        }
+        "Вернуть ударение словаря" => "Back to the dictionary stress",
+        "Недавние запросы" => "Recent searches",
+        "Заметка к запросу" => "Note on the query",
+        "Заметка" => "Note",
+        "Заметка…" => "Note…",
+        "Коннотации, где уже использовано…" => {
+            "Connotations, where it was already used…"
+        }
+        "Сравнить метрики" => "Compare metrics",
+        "Ранжировать рифмы только по звучанию, только по смыслу и вместе" => {
+            "Rank rhymes by sound only, by meaning only and by both"
+        }
+        "Сравнение метрик" => "Metrics comparison",
+        "Звучание" => "Sound",
+        "Смысл" => "Meaning",
+        "Вместе" => "Both",
+        "Созвучия" => "Sound alike",
+        "Слова с похожими согласными, без учёта ударений и темы" => {
+            "Words with similar consonants, ignoring stresses and the theme"
+        }
+        "Уточняется…" => "Refining…",
+        "Старые результаты" => "Old results",
+        "Найдены в прошлый раз, настройки могли измениться" => {
+            "Found last time, the settings may have changed"
+        }
+        "Результаты устарели" => "Results are outdated",
+        "Обновить" => "Refresh",
+        "Тип рифмы" => "Rhyme type",
+        "Найдено" => "Found",
+        "показаны первые" => "showing the first",
+        "Любой" => "Any",
+        "Мужская" => "Masculine",
+        "Женская" => "Feminine",
+        "Дактилическая" => "Dactylic",
+        "Гипердактилическая" => "Hyperdactylic",
+        "Копировать все" => "Copy all",
+        "Копировать" => "Copy",
+        "Экспорт" => "Export",
+        "Файлы" => "Files",
+        "Сохранить результаты в файл" => "Save the results to a file",
+        "Созвучные слова" => "Similar sounding words",
+        "Менее точные" => "Less exact",
+        "Отличные" => "Excellent",
+        "Хорошие" => "Good",
+        "Приемлемые" => "Acceptable",
+        "Вставить в текст" => "Insert into the text",
+        "Вставить в текст, правый клик — другие действия" => {
+            "Insert into the text, right click — other actions"
+        }
+        "Вставить в другой форме" => "Insert in another form",
+        "Других форм не найдено" => "No other forms found",
+        "Искать рифмы к слову" => "Search rhymes for the word",
+        "Искать рифмы к этому слову" => "Search rhymes for this word",
+        "Добавить слово" => "Add the word",
+        "Указать ударение и научить словарь этому слову" => {
+            "Set the stress and teach the dictionary this word"
+        }
+        "Убрать их из темы" => "Remove them from the theme",
+        "Удалить неизвестные слова и повторить поиск" => {
+            "Remove the unknown words and search again"
+        }
+
+        // themes
+        "Встроенная тема" => "Built-in theme",
+        "Начните вводить название темы, Enter — выбрать первую" => {
+            "Start typing the theme name, Enter — pick the first one"
+        }
+        "Без темы" => "No theme",
+        "Пользовательская" => "Custom",
+        "Сохранённые" => "Saved",
+        "Ничего не найдено" => "Nothing found",
+        "Удалить сохранённую тему" => "Delete the saved theme",
+        "Введите слова, ассоциирующиеся с этой темой" => {
+            "Enter words associated with this theme"
+        }
+        "Слова, которых нет в словаре, выделены красным" => {
+            "Words missing from the dictionary are marked red"
+        }
+        "Название" => "Name",
+        "Сохранить тему" => "Save theme",
+
+        // settings
+        "Параметры подбора рифмы" => "Rhyme search settings",
+        "Количество отображаемых рифм" => "Number of rhymes shown",
+        "Сбросить" => "Reset",
+        "Сохранить настройки" => "Save settings",
+        "Загрузить настройки" => "Load settings",
+        "Не удалось прочитать настройки" => "Can't read the settings",
+        "Вернуть значения по умолчанию" => "Restore the default values",
+        "Пресеты" => "Presets",
+        "Название пресета" => "Preset name",
+        "Сохранить" => "Save",
+        "Загрузить" => "Load",
+        "Удалить" => "Delete",
+        "Индексация гласных" => "Vowel indexation",
+        "Показывать созвучные слова (омофоны)" => {
+            "Show similar sounding words (homophones)"
+        }
+        "Показывать примеры строк (шаблонные)" => {
+            "Show example lines (templated)"
+        }
+        "Показывать ударения" => "Show stresses",
+        "Размер шрифта" => "Font size",
+        "Добирать менее точные рифмы" => "Fill up with less exact rhymes",
+        "Искать на лету" => "Search while typing",
+        "Слогов в рифме" => "Syllables in a rhyme",
+        "Оценка рифм: отличные до" => "Rhyme scores: excellent up to",
+        "хорошие до" => "good up to",
+        "Чем меньше оценка, тем лучше рифма" => {
+            "The lower the score, the better the rhyme"
+        }
+        "от " => "from ",
+        "до " => "to ",
+        "0 — без ограничения" => "0 — no limit",
+        "Части речи в рифме" => "Parts of speech in a rhyme",
+        "По умолчанию" => "Default",
+        "Нейтрально" => "Neutral",
+        "Избегать однокоренных глаголов" => "Avoid cognate verbs",
+        "Избегать одинаковых частей речи" => {
+            "Avoid same parts of speech"
+        }
+        "Свои значения" => "Custom values",
+        "Веса" => "Weights",
+        "Вес" => "Weight",
+        "Степень" => "Power",
+        "Тематика" => "Theme",
+        "Степень для одного слова" => "Power for a single word",
+        "Множитель для одного слова" => "Multiplier for a single word",
+        "Разное" => "Miscellaneous",
+        "Близкая длина" => "Similar length",
+        "Совпадающие гласные/согласные в конце" => {
+            "Same vowels/consonants at the end"
+        }
+        "Популярность слова" => "Word popularity",
+        "Ударения" => "Stresses",
+        "Строгие ударения" => "Strict stresses",
+        "Нестрогие ударения" => "Loose stresses",
+        "Штраф за плохой ритм" => "Bad rythm penalty",
+        "Сдвиг веса сравнения гласных" => "Vowel comparison weight shift",
+        "Степень веса сравнения гласных" => {
+            "Vowel comparison weight power"
+        }
+        "Ассимптотика метрики" => "Metric asymptotics",
+        "Сдвиг ассимптотики метрики" => "Metric asymptotics shift",
+        "Сдвиг ассимптотики" => "Asymptotics shift",
+        "Длина искомой рифмы" => "Rhyme length",
+        "Идеальная длина" => "Ideal length",
+        "Вес штрафа меньших" => "Shorter penalty weight",
+        "Степень штрафа меньших" => "Shorter penalty power",
+        "Вес штрафа больших" => "Longer penalty weight",
+        "Степень штрафа больших" => "Longer penalty power",
+        "Штрафы за совпадающие части речи" => {
+            "Same part of speech penalties"
+        }
+        "Структура" => "Structure",
+        "Степень разности длин слогов" => {
+            "Syllable length difference power"
+        }
+        "Сдвиг множителя сравнения с конца" => {
+            "Comparison from the end multiplier shift"
+        }
+        "Степень множителя сравнения с конца" => {
+            "Comparison from the end multiplier power"
+        }
+        "Сдвиг расстояния в слове между буквами" => {
+            "Letter distance in a word shift"
+        }
+        "Степень расстояния в слове между буквами" => {
+            "Letter distance in a word power"
+        }
+        "Сдвиг важности согласных в концовке" => {
+            "Ending consonants importance shift"
+        }
+        "Степень важности согласных в концовке" => {
+            "Ending consonants importance power"
+        }
+        "Штраф за дополнительные звуки" => "Extra sounds penalty",
+
+        // parts of speech
+        "Исключить части речи" => "Exclude parts of speech",
+        "Исключить эту часть речи и повторить поиск" => {
+            "Exclude this part of speech and search again"
+        }
+        "Существительные" => "Nouns",
+        "Прилагательные" => "Adjectives",
+        "Местоимения" => "Pronouns",
+        "Местоимения-прилагательные" => "Pronominal adjectives",
+        "Глаголы" => "Verbs",
+        "Наречия" => "Adverbs",
+        "Числительные" => "Numerals",
+        "Счётные прилагательные" => "Ordinal adjectives",
+        "Вводные" => "Parenthetical words",
+        "Междометия" => "Interjections",
+        "Предикативы" => "Predicatives",
+        "Предлоги" => "Prepositions",
+        "Союзы" => "Conjunctions",
+        "Сравнительные степени" => "Comparatives",
+        "Частицы" => "Particles",
+        "Прочее (фразеологизмы, устаревшие…)" => {
+            "Other (idioms, archaic…)"
+        }
+        "Загрузка словаря…" => "Loading the dictionary…",
+        "Подобрать для всех строк" => "Rhymes for every line",
+        "Рифмы для всех строк" => "Rhymes for every line",
+        "Пересчитать для изменённых строк" => {
+            "Recompute for the changed lines"
+        }
+        "Исключённые слова" => "Excluded words",
+        "Вернуть в результаты" => "Return to the results",
+        "Исключить" => "Exclude",
+        "Слово" => "Word",
+        "Ctrl+F — к запросу, Escape — обратно к тексту" => {
+            "Ctrl+F — to the query, Escape — back to the text"
+        }
+        "Цвет выделения" => "Accent color",
+        "Разбирать оценки рифм" => "Break the rhyme scores down",
+        "Из чего складывается оценка, при наведении на рифму; поиск медленнее" => {
+            "What the score is made of, on hovering a rhyme; slows the search down"
+        }
+        "Оценка" => "Score",
+        "Прочее" => "Other",
+        "Тематический поиск недоступен" => {
+            "Search by theme is unavailable"
+        }
+        "Не удалось загрузить векторы слов, рифмы ищутся без учёта темы" => {
+            "Couldn't load the word vectors, rhymes are searched without a theme"
+        }
+        "Копилка" => "Collected",
+        "Отложить в копилку" => "Put aside",
+        "Убрать из копилки" => "Remove from the collected",
+        "Символов" => "Characters",
+        "Слов" => "Words",
+        "Строк" => "Lines",
+        "Слогов" => "Syllables",
+        "Ударения строк и размер" => "Line stresses and meter",
+        "Определять размер сам" => "Detect the meter",
+        "Размер" => "Meter",
+        "Ямб" => "Iamb",
+        "Хорей" => "Trochee",
+        "Дактиль" => "Dactyl",
+        "Амфибрахий" => "Amphibrach",
+        "Анапест" => "Anapest",
+        "Предпросмотр" => "Preview",
+        "Лучшие рифмы последнего запроса с новыми параметрами, пересчитываются на лету; медленно" => {
+            "Top rhymes of the last query with the new settings, updated live; slow"
+        }
+        "Сравнить с эталоном" => "Compare with the baseline",
+        "Ранжировать рифмы эталонными параметрами и текущими" => {
+            "Rank the rhymes with the baseline settings and with the current ones"
+        }
+        "Запомнить как эталон" => "Keep as the baseline",
+        "Чтобы сравнивать с ним результаты изменённых параметров" => {
+            "To compare the results of the changed settings with it"
+        }
+        "Вернуть эталон" => "Restore the baseline",
+        "Сравнение с эталоном" => "Comparison with the baseline",
+        "Эталон" => "Baseline",
+        "Текущие" => "Current",
+        "Показать ещё" => "Show more",
+        "Точная рифма" => "Exact rhyme",
+        "Только рифмы с совпадающими ударениями; параметры ударений не меняются" => {
+            "Only rhymes with matching stresses; the stress settings stay as they are"
+        }
+        "Насколько важна близость рифмы к теме по смыслу; больше — ближе к теме" => "How much the rhyme's closeness to the theme matters; more keeps closer to the theme",
+        "Насколько выше поднимаются частые слова; больше — реже редкие слова" => "How much common words are raised; more shows rare words less often",
+        "Вес совпадения гласных и места ударения; больше — точнее созвучие гласных" => "Weight of matching vowels and stress position; more makes the vowels match closer",
+        "Вес совпадения согласных между гласными; больше — строже к согласным" => "Weight of matching consonants between the vowels; more is stricter about consonants",
+        "Вес похожих согласных в концовке; больше — строже к согласным концовки" => "Weight of similar consonants in the ending; more is stricter about them",
+        "Насколько эта часть влияет на итоговую оценку; 0 — не влияет совсем" => "How much this part affects the total score; 0 turns it off",
+        "Степень, в которую возводится расхождение; больше — сильнее наказываются большие расхождения" => "Power the difference is raised to; more punishes big differences harder",
+        "То же для темы из одного слова" => "The same for a single word theme",
+        "Во сколько раз иначе учитывается тема из одного слова; больше — сильнее" => "How differently a single word theme counts; more counts it stronger",
+        "Штраф за разницу в длине слов; больше — ближе по длине" => "Penalty for words of different lengths; more keeps the lengths closer",
+        "Поощрение одинакового последнего звука; больше — важнее одинаковый конец" => "Reward for the same last sound; more makes the same ending more important",
+        "Штраф за несовпадение ударных гласных; больше — строже" => "Penalty for different stressed vowels; more is stricter",
+        "Штраф за несовпадение безударных гласных; больше — строже" => "Penalty for different unstressed vowels; more is stricter",
+        "Штраф за ударение на другом слоге от конца; больше — строже к ритму" => "Penalty for the stress on another syllable from the end; more is stricter about the rythm",
+        "Насколько значимы гласные дальше от конца; больше — вес всех гласных ровнее" => "How much the vowels further from the end matter; more weighs all vowels more evenly",
+        "Как быстро падает важность гласных к началу слова; больше — важна только концовка" => "How fast the vowels lose importance towards the start; more leaves only the ending important",
+        "Как быстро штраф выходит на предел; больше — большие расхождения наказываются сильнее" => "How fast the penalty levels off; more punishes big differences harder",
+        "С какого расхождения штраф начинает выходить на предел; больше — мелкие различия прощаются" => "From which difference the penalty starts to level off; more forgives small differences",
+        "Длина рифмы в буквах, за которую нет штрафа" => "Rhyme length in letters that isn't penalized",
+        "Штраф за рифмы короче идеальной; больше — короткие реже" => "Penalty for rhymes shorter than the ideal; more shows short ones less often",
+        "Как быстро растёт штраф за короткие рифмы; больше — быстрее" => "How fast the penalty for short rhymes grows; more grows faster",
+        "Штраф за рифмы длиннее идеальной; больше — длинные реже" => "Penalty for rhymes longer than the ideal; more shows long ones less often",
+        "Как быстро растёт штраф за длинные рифмы; больше — быстрее" => "How fast the penalty for long rhymes grows; more grows faster",
+        "Штраф, если рифма той же части речи, что и запрос; больше — разнообразнее рифмы" => "Penalty for a rhyme of the same part of speech as the query; more makes the rhymes more varied",
+        "Как сильно наказывается разное число согласных между гласными; больше — строже" => "How hard a different number of consonants between the vowels is punished; more is stricter",
+        "Насколько значимы согласные дальше от конца; больше — вес всех согласных ровнее" => "How much the consonants further from the end matter; more weighs all consonants more evenly",
+        "Как быстро падает важность согласных к началу слова; больше — важна только концовка" => "How fast the consonants lose importance towards the start; more leaves only the ending important",
+        "Насколько прощается сдвиг похожих согласных внутри слова; больше — мягче" => "How much a shift of similar consonants within the word is forgiven; more is softer",
+        "Как быстро растёт штраф за сдвиг согласных; больше — строже к их порядку" => "How fast the penalty for shifted consonants grows; more is stricter about their order",
+        "Штраф за согласные, которых нет в другом слове; больше — строже" => "Penalty for consonants missing from the other word; more is stricter",
+        "Вес задан не числом, результаты непредсказуемы" => {
+            "A weight is not a number, the results are unpredictable"
+        }
+        "Отрицательный вес поднимает наверх худшие рифмы" => {
+            "A negative weight raises the worst rhymes to the top"
+        }
+        "Все веса нулевые, рифмы никак не ранжируются" => {
+            "All the weights are zero, the rhymes aren't ranked at all"
+        }
+        "Звучание не учитывается, результаты не будут рифмами" => {
+            "The sound isn't taken into account, the results won't rhyme"
+        }
+        "Один из весов намного больше обычного, остальные почти не влияют" => {
+            "One of the weights is far above the usual, the others hardly matter"
+        }
+        "По слову в строке" => "A word per line",
+        "Нумерованный список" => "Numbered list",
+        "Через запятую" => "Comma separated",
+        "Порядок" => "Order",
+        "По оценке" => "By score",
+        "По популярности" => "By popularity",
+        "Популярность" => "Popularity",
+        "Допуск неточных рифм" => "Near rhyme tolerance",
+        "Один ползунок вместо многих: от точных рифм до самых вольных" => {
+            "One slider instead of many: from exact rhymes to the loosest ones"
+        }
+        "Показать похожие слова темы" => "Show words close to the theme",
+        "Восстановлен черновик" => "Draft restored",
+        "Текст не успел сохраниться при закрытии" => "The text wasn't saved when the app was closed",
+        "Экспорт с разбором" => "Export with analysis",
+        "Текст со схемой рифмовки и ударениями строк" => {
+            "The text with its rhyme scheme and the stresses of the lines"
+        }
+        "Строка" => "Line",
+        "Рифма" => "Rhyme",
+        "Без однокоренных слов" => "No words of the same root",
+        "Не предлагать формы запроса и слова с тем же корнем, как любить — полюбить" => {
+            "Don't suggest forms of the query and words of its root, like любить — полюбить"
+        }
+        "Введите слово, чтобы подобрать к нему рифмы" => "Type a word to find rhymes to it",
+        "Рифмы" => "Rhymes",
+        "Все части речи" => "All parts of speech",
+        "Без служебных слов" => "No function words",
+        "Только значимые слова" => "Content words only",
+        "Сколько искать" => "How many to search",
+        "Из скольких лучших рифм отбирать показанные; больше — меньше пустеет список при строгих фильтрах, но медленнее" => {
+            "How many of the best rhymes the shown ones are picked from; more keeps the list full with strict filters, but is slower"
+        }
+        "🎲 Удиви меня" => "🎲 Surprise me",
+        "Вставить неожиданную рифму: пореже и подальше от начала списка" => {
+            "Insert an unexpected rhyme: rarer and further down the list"
+        }
+        "Креативность" => "Creativity",
+        "Как далеко вниз по списку заходить" => "How far down the list to reach",
+        "Автоматически" => "Automatically",
+        "Знак ударения" => "Accent mark",
+        "Заглавная гласная" => "Capital vowel",
+        "Апостроф" => "Apostrophe",
+        "Не различать е и ё" => "Treat е and ё alike",
+        "Показывать написания с е и с ё одной рифмой" => "Show the spellings with е and ё as one rhyme",
+        "Свои слова…" => "Custom words…",
+        "Свои слова" => "Custom words",
+        "По слову в строке, апостроф после ударной гласной: глока'я" => {
+            "A word per line, an apostrophe after the stressed vowel: глока'я"
+        }
+        "Без отмеченного ударения" => "Without a marked stress",
+        "Добавить" => "Add",
+        "Прочитать вслух" => "Read aloud",
+        "Читать слова вслух" => "Read words aloud",
+        "Выпали из результатов" => "Dropped from the results",
+        "Изменение места с прошлого поиска" => "Change of the place since the last search",
+        "Показывать изменения" => "Show changes",
+        "Отмечать, как сдвинулись рифмы при повторном поиске того же слова" => {
+            "Mark how the rhymes moved when the same word is searched again"
+        }
+        "Значение слова" => "Meaning of the word",
+        "Словарь значений" => "Dictionary of meanings",
+        "Адрес страницы слова, {} заменяется самим словом" => {
+            "Address of the page of a word, {} is replaced with the word"
+        }
+        "Рифма до конца" => "Rhyme to the end",
+        "Рифма по ударному слогу" => "Rhyme to the stressed syllable",
+        "Что должно совпадать: всё после ударной гласной или в основном ударный слог" => "What has to match: everything after the stressed vowel or mostly the stressed syllable",
+        "Экспорт истории (CSV)" => "Export history (CSV)",
+        "Все запросы этого сеанса со временем, пресетом и лучшей рифмой" => "All the searches of this session with the time, preset and top rhyme",
+        "Время" => "Time",
+        "Запрос" => "Query",
+        "Пресет" => "Preset",
+        "Лучшая рифма" => "Top rhyme",
+        "Слишком длинный запрос, букв больше чем" => "The query is too long, more letters than",
+        "Введите слово кириллицей" => "Type the word in Cyrillic",
+        "Слова нет в словаре" => "The dictionary doesn't know the word",
+        "Тема пуста, введите хотя бы одно слово" => "The theme is empty, type at least one word",
+        "Неизвестное слово в теме" => "Unknown word in the theme",
+        "Неизвестные слова в теме" => "Unknown words in the theme",
+        "Справка" => "Reference",
+        "Текст рядом со стихотворением: задание или оригинал перевода" => "Text beside the poem: a brief or the source of a translation",
+        "Править справку" => "Edit the reference",
+        "Включите правку и вставьте задание или текст оригинала" => "Turn on editing and paste the brief or the source text",
+        "Среди рифм изменения появятся после перезапуска" => "The rhymes will include the changes after a restart",
+        "В темах свои слова не работают: их смысл словарю неизвестен" => "Custom words don't work in themes: the dictionary doesn't know their meaning",
+        "Подсветка схемы рифмовки отмечает строки, рифмующиеся не по ней" => "The rhyme scheme highlighting marks the lines rhyming against it",
+        "Вернуть сохранённый текст" => "Bring back the saved text",
+        "Браузер не дал скачать файл" => "The browser didn't let the file download",
+        "Не удалось сохранить файл" => "Couldn't save the file",
+        "Новое слово" => "New word",
+        "Ударный слог:" => "Stressed syllable:",
+        "Катрен, перекрёстная рифма" => "Quatrain, alternate rhyme",
+        "Катрен, парная рифма" => "Quatrain, couplet rhyme",
+        "Катрен, опоясывающая рифма" => "Quatrain, enclosed rhyme",
+        "Сонет (французский)" => "Sonnet (French)",
+        "Сонет (шекспировский)" => "Sonnet (Shakespearean)",
+        "Онегинская строфа" => "Onegin stanza",
+        "Лимерик" => "Limerick",
+        _ => return None,
+    })
+}
@@ -0,0 +1,112 @@
+use quickpoeter::finder::WordCollector;
+
+use super::prosody::is_vowel;
+
+/// Endings cut off to get the stem shared by the forms of a word.
+const ENDING_LETTERS: &str = "аеёиоуыэюяйь";
+
+/// Forms which can't have longer endings than this, in letters.
+const MAX_ENDING: usize = 4;
+const MAX_FORMS: usize = 20;
+
+/// Shortest stem taken for a root, shorter ones are in too many words.
+const MIN_ROOT: usize = 3;
+
+/// Verb and noun prefixes. Rhymes end alike anyway, so only these may tell
+/// the words apart: билет isn't лет with a prefix.
+const PREFIXES: [&str; 36] = [
+    "без", "бес", "в", "вз", "вс", "воз", "вос", "вы", "до", "за", "из", "ис", "на", "над", "не",
+    "недо", "низ", "нис", "о", "об", "от", "пере", "по", "под", "пре", "пред", "при", "про", "раз",
+    "рас", "с", "у", "съ", "объ", "подъ", "разъ",
+];
+
+/// Forms of the prefixes before consonant clusters, as in вогнать and собрать.
+const PREFIXES_BEFORE_CLUSTERS: [&str; 8] =
+    ["во", "со", "обо", "ото", "подо", "надо", "изо", "разо"];
+
+/// Whether the words look like one root with different prefixes, as любить and
+/// полюбить: the stems differ only by prefixes from the lists. A guess as well.
+pub fn same_root(a: &str, b: &str) -> bool {
+    let stem = |w: &str| {
+        w.to_lowercase()
+            .trim_end_matches(|c| ENDING_LETTERS.contains(c))
+            .to_string()
+    };
+    let (a, b) = (stem(a), stem(b));
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    let Some(prefix) = long.strip_suffix(short.as_str()) else {
+        return false;
+    };
+    // one-letter prefixes turn too many short words into others: рок and урок
+    let min_root = match prefix.chars().count() {
+        1 => MIN_ROOT + 1,
+        _ => MIN_ROOT,
+    };
+    short.chars().count() >= min_root && prefixes(prefix, &short)
+}
+
+/// Whether `letters` are prefixes, one after another, put before `root`. Only
+/// the outermost one may be a single letter, or ворот would be в-о-рот.
+fn prefixes(letters: &str, root: &str) -> bool {
+    if letters.is_empty() {
+        return true;
+    }
+    let cluster = root.chars().take(2).filter(|&c| !is_vowel(c)).count() == 2;
+    let before_clusters = PREFIXES_BEFORE_CLUSTERS.iter().filter(|_| cluster);
+    PREFIXES.iter().chain(before_clusters).any(|p| {
+        letters.strip_suffix(p).is_some_and(|rest| {
+            (rest.is_empty() || p.chars().count() > 1) && prefixes(rest, &format!("{p}{root}"))
+        })
+    })
+}
+
+/// Other forms of `word` known to the dictionary: words of the same part of
+/// speech sharing its stem. The dictionary has no paradigms, so it is a guess.
+pub fn forms(collector: &WordCollector, word: &str) -> Vec<String> {
+    let Some(source) = collector.get_word(word) else {
+        return vec![];
+    };
+
+    let stem = word.trim_end_matches(|c| ENDING_LETTERS.contains(c));
+    let stem_len = stem.chars().count();
+    if stem_len < 3 {
+        return vec![];
+    }
+
+    let mut forms: Vec<String> = collector
+        .words
+        .iter()
+        .filter(|w| {
+            w.src != word
+                && w.speech_part == source.speech_part
+                && w.src.starts_with(stem)
+                && w.src.chars().count() <= stem_len + MAX_ENDING
+        })
+        .map(|w| w.src.clone())
+        .collect();
+
+    forms.sort_by_key(|w| (w.chars().count(), w.clone()));
+    forms.truncate(MAX_FORMS);
+    forms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::same_root;
+
+    #[test]
+    fn prefixed_words_share_the_root() {
+        assert!(same_root("любить", "полюбить"));
+        assert!(same_root("брать", "собрать"));
+        assert!(same_root("стать", "встать"));
+    }
+
+    #[test]
+    fn words_ending_alike_are_not_prefixed() {
+        for word in ["билет", "балет", "скелет", "пистолет"] {
+            assert!(!same_root("лет", word), "{word}");
+        }
+        assert!(!same_root("рот", "ворот"));
+        assert!(!same_root("рок", "урок"));
+    }
+}
@@ -1,13 +1,42 @@
+use custom_words::{CustomWords, NewWord};
+use editor::EnterBehavior;
+use error::SearchError;
+use export::ResultStyle;
+use highlighter::{HighlightMode, Highlighter};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
 use egui::{Color32, ComboBox, FontId, RichText, Slider, TextEdit, Ui};
+use i18n::{tr, Language};
 use lazy_static::lazy_static;
+use poll_promise::Promise;
+use prosody::{Meter, RhymeType, StressMark};
 use quickpoeter::{
-    api::{find, string2word},
-    finder::WordCollector,
+    api::string2word,
+    finder::{Word, WordCollector},
     meaner::MeanTheme,
     reader::{GeneralSettings, MeanStrThemes},
 };
+use search::{
+    FoundWords, LineRhymes, PendingSearch, Rhyme, RhymeMode, SearchQuery, SettingsPreview,
+};
+use session_log::{LoggedSearch, SessionLog};
+use templates::PoemTemplate;
 
+mod custom_words;
+mod editor;
+mod error;
+mod export;
+mod help;
 mod highlighter;
+mod homophones;
+mod i18n;
+mod inflection;
+mod presets;
+mod prosody;
+mod search;
+mod session_log;
+mod speech;
+mod templates;
 
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -16,22 +45,250 @@ pub struct QuickpoeterApp {
     #[serde(skip)]
     rhyme_word: String,
     #[serde(skip)]
-    rhyme_output: Result<Vec<String>, String>,
+    rhyme_output: Result<Vec<Rhyme>, SearchError>,
+    /// Search running in the background; displayed results are stale or a preview meanwhile.
     #[serde(skip)]
-    show_settings: bool,
+    pending: Option<PendingSearch>,
+    /// Dictionary being loaded in the background, `None` before the first frame and after.
     #[serde(skip)]
-    show_theme: bool,
+    dictionary_loading: Option<Promise<()>>,
+    #[serde(skip)]
+    dictionary_loaded: bool,
+    #[serde(skip)]
+    homophones: Vec<String>,
+    /// Parts of the scores of the displayed rhymes, if asked for.
+    #[serde(skip)]
+    score_parts: HashMap<String, [f32; 5]>,
+    /// Results of the loose pass, filling up a short strict list.
+    #[serde(skip)]
+    loose_output: Vec<String>,
+    /// How many of the results are laid out, grows a page at a time.
+    #[serde(skip)]
+    results_shown: usize,
+    /// Word and its forms shown in the result context menu.
+    #[serde(skip)]
+    inflections: Option<(String, Vec<String>)>,
+    /// Query the dictionary failed to recognize.
+    #[serde(skip)]
+    unknown_query: Option<String>,
+    /// How many rhymes the search matched before they were cut to the shown ones.
+    #[serde(skip)]
+    found_total: Option<usize>,
+    #[serde(skip)]
+    new_word: Option<NewWord>,
+    /// Words with their stress marked, typed to be added to the custom ones;
+    /// `None` when the window is closed.
+    #[serde(skip)]
+    custom_words_input: Option<String>,
+    #[serde(skip)]
+    new_template: Option<PoemTemplate>,
+    /// Word and the text of its note being edited.
+    #[serde(skip)]
+    editing_note: Option<(String, String)>,
+    /// Displayed results come from the previous session.
+    #[serde(skip)]
+    restored_results: bool,
+    #[serde(skip)]
+    highlighter: Highlighter,
+    #[serde(skip)]
+    history: editor::History,
+    #[serde(skip)]
+    counter: editor::Counter,
+    /// Query word and how the dictionary understood it, or why it didn't.
+    #[serde(skip)]
+    query_info: Option<(String, Result<Word, String>)>,
+    /// Stress picked for the query word over the dictionary's one, with the word it's for.
+    #[serde(skip)]
+    query_stress: Option<(String, usize)>,
+    /// The same query ranked by sound only, by meaning only and by both.
+    #[serde(skip)]
+    comparison: Option<Promise<[FoundWords; 3]>>,
+    /// Words closest to the theme with its words joined, see [`search::theme_neighbours`].
+    #[serde(skip)]
+    theme_neighbours: Option<(String, Promise<FoundWords>)>,
+    #[serde(skip)]
+    line_rhymes: LineRhymes,
+    /// Re-rank the last search live as the settings are edited.
+    #[serde(skip)]
+    preview_settings: bool,
+    #[serde(skip)]
+    settings_preview: SettingsPreview,
+    /// Lines of the main text and the core words of their endings, shown with their rhymes.
+    #[serde(skip)]
+    line_endings: Option<Vec<(String, String)>>,
+    /// The query ranked with the baseline settings and with the current ones.
+    #[serde(skip)]
+    baseline_comparison: Option<Promise<[FoundWords; 2]>>,
+    /// Words sounding like the query, found with ad-hoc settings.
+    #[serde(skip)]
+    sound_alike: Option<Promise<FoundWords>>,
+    /// Line of the editor caret, to notice when it moves to another one.
+    #[serde(skip)]
+    cursor_line: Option<usize>,
+    #[serde(skip)]
+    cursor_moved_at: Option<f64>,
+    /// Time of the last edit of the query not searched yet.
+    #[serde(skip)]
+    query_edited_at: Option<f64>,
+    /// Settings file being opened.
+    #[serde(skip)]
+    settings_file: Option<Promise<Option<String>>>,
+    #[serde(skip)]
+    settings_file_error: Option<String>,
+    /// State of the random word generator, seeded on first use.
+    #[serde(skip)]
+    rng: u64,
+    /// Confirmation of wiping the data is open; `true` once the user ticked "I understand".
+    #[serde(skip)]
+    clearing_data: Option<bool>,
+    /// Text and time of the last autosave, see [`DRAFT_KEY`].
+    #[serde(skip)]
+    autosaved: (String, f64),
+    /// The saved text a draft was restored over, offered back in a toast.
+    #[serde(skip)]
+    draft_replaced: Option<String>,
+    /// Why the last export failed, shown until dismissed.
+    #[serde(skip)]
+    export_error: Option<String>,
+    /// What the displayed results were searched with.
+    #[serde(skip)]
+    searched: Option<SearchQuery>,
+    #[serde(skip)]
+    theme_filter: String,
+    /// The list of themes was open the last frame.
+    #[serde(skip)]
+    theme_list_open: bool,
+    /// Word typed to be added to the excluded ones.
+    #[serde(skip)]
+    new_excluded_word: String,
+    #[serde(skip)]
+    preset_name: String,
+    /// Name to save the excluded parts of speech under.
+    #[serde(skip)]
+    rps_preset_name: String,
+    /// Name to save the custom theme under, and why saving failed.
+    #[serde(skip)]
+    user_theme_name: String,
+    #[serde(skip)]
+    user_theme_error: Option<SearchError>,
     #[serde(skip)]
     general_settings: GeneralSettings,
+    /// Keeps the query from being replaced, so its rhymes can be inserted many times.
+    #[serde(skip)]
+    query_locked: bool,
+    /// Previous queries, most recent last.
+    #[serde(skip)]
+    query_back: Vec<String>,
+    /// Queries left by going back, most recent last.
+    #[serde(skip)]
+    query_forward: Vec<String>,
 
     custom_theme_text: String,
     theme: Theme,
+    /// Custom themes saved by the user: name → words.
+    user_themes: BTreeMap<String, String>,
     rps: RemovePartsOfSpeech,
+    /// Combinations of the excluded parts of speech saved by the user.
+    rps_presets: BTreeMap<String, RemovePartsOfSpeech>,
+    /// Settings kept to compare the edited ones with.
+    baseline: Option<GeneralSettings>,
+    presets: presets::Presets,
+    custom_words: CustomWords,
+    /// User's notes on words: connotations, where they were already used…
+    notes: HashMap<String, String>,
+    templates: Vec<PoemTemplate>,
+    /// Results of the most recent search, shown right after restart.
+    last_results: Option<LastResults>,
+    /// Successfully searched words, the latest first.
+    recent_searches: Vec<String>,
+    /// Rhymes put aside to be used later.
+    collected: Vec<String>,
+    /// How the results are copied and exported.
+    result_style: ResultStyle,
+    /// Rhyme scheme of the last inserted template, a letter per line.
+    expected_scheme: Option<String>,
     show_rhymes: u32,
+    /// Candidates to filter the shown rhymes from, see [`SearchQuery::search_rhymes`].
+    search_rhymes: u32,
+    /// Share of the results "Surprise me" picks from, the top ones to all of them.
+    creativity: f32,
+    show_homophones: bool,
+    show_examples: bool,
+    /// Explain the scores of the results on hover; costs a search per part of the score.
+    show_score_parts: bool,
+    /// Mark how the results moved since the last search of the same word.
+    show_changes: bool,
+    /// Searches of this session, for researchers to export.
+    #[serde(skip)]
+    session_log: SessionLog,
+    /// Results of the last search of the word searched now.
+    #[serde(skip)]
+    results_before: Option<ResultsBefore>,
+    /// Mark the stressed vowel in the results.
+    show_stress: bool,
+    /// How it's marked, fonts differ in drawing the accent.
+    stress_mark: StressMark,
+    /// Offer to read the words aloud, where the browser can.
+    read_aloud: bool,
+    /// Where the meanings of words are looked up, `{}` standing for the word.
+    dictionary_url: String,
+    /// Size of the results and the main text.
+    font_size: f32,
+    show_settings: bool,
+    show_theme: bool,
+    /// Width of the results panel, as the user left it.
+    panel_width: f32,
+    /// The rhymes drawer of the compact layout is pulled out.
+    drawer_open: bool,
+    /// Fill short result lists with rhymes found with relaxed settings.
+    loose_pass: bool,
+    /// Search with the strict stress settings instead of the user's ones.
+    strict_rhyme: bool,
+    /// Leave out the rhymes of the same root as the query.
+    exclude_same_root: bool,
+    /// Show the spellings with е and ё as one rhyme.
+    merge_yo: bool,
+    /// Percent of the master strictness slider, see [`search::tolerant`].
+    /// Not kept, just like the settings it was applied to.
+    #[serde(skip)]
+    tolerance: f32,
+    rhyme_mode: RhymeMode,
+    /// Syllable count limits of the rhymes, 0 as the maximum means no limit.
+    min_syllables: usize,
+    max_syllables: usize,
+    /// Words never shown in the results, with their forms.
+    excluded_words: Vec<String>,
+    /// Highest scores of excellent and good rhymes.
+    quality_thresholds: [f32; 2],
+    /// Show only rhymes of this type.
+    rhyme_type_filter: Option<RhymeType>,
+    result_order: ResultOrder,
+    enter_behavior: EnterBehavior,
+    /// Search rhymes for the line with the caret.
+    follow_cursor: bool,
+    /// Search while the query is typed, without waiting for Enter.
+    live_search: bool,
+    highlight_mode: HighlightMode,
+    alliteration_radius: usize,
+    /// Show the stress patterns of the lines beside them.
+    show_meter: bool,
+    /// A brief or the source of a translation, shown beside the poem.
+    reference_text: String,
+    show_reference: bool,
+    /// The reference is read-only unless unlocked, not to be edited instead of the poem.
+    #[serde(skip)]
+    editing_reference: bool,
+    /// Meter the lines are checked against, detected from the first lines if `None`.
+    meter: Option<Meter>,
+    language: Language,
+    dark_mode: bool,
+    /// Color of the search error and the result highlights.
+    accent: Color32,
     main_text: String,
 }
 
-#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[derive(serde::Deserialize, serde::Serialize, Default, Clone, PartialEq)]
+#[serde(default)]
 struct RemovePartsOfSpeech {
     /// с      существительное
     /// п      прилагательное
@@ -97,12 +354,72 @@ impl RemovePartsOfSpeech {
         add!(self.misc, "?");
         v
     }
+
+    /// Common combinations, offered along with the user's ones.
+    fn builtin_presets() -> [(&'static str, Self); 3] {
+        let function_words = Self {
+            prep: true,
+            conj: true,
+            part: true,
+            citoslovce: true,
+            ..Self::default()
+        };
+        let meaningful = Self {
+            pronoun: true,
+            pronoun_adj: true,
+            linking: true,
+            pred: true,
+            misc: true,
+            ..function_words.clone()
+        };
+        [
+            ("Все части речи", Self::default()),
+            ("Без служебных слов", function_words),
+            ("Только значимые слова", meaningful),
+        ]
+    }
+
+    /// Flag of the part of speech with the dictionary code, see [`Self::get_list`].
+    fn flag(&mut self, code: &str) -> Option<&mut bool> {
+        Some(match code {
+            "с" => &mut self.noun,
+            "п" => &mut self.adj,
+            "мс" => &mut self.pronoun,
+            "мс-п" => &mut self.pronoun_adj,
+            "г" => &mut self.verb,
+            "н" => &mut self.adv,
+            "числ" => &mut self.num,
+            "числ-п" => &mut self.num_adj,
+            "вводн" => &mut self.linking,
+            "межд" => &mut self.citoslovce,
+            "предик" => &mut self.pred,
+            "предл" => &mut self.prep,
+            "союз" => &mut self.conj,
+            "сравн" => &mut self.compare,
+            "част" => &mut self.part,
+            "?" => &mut self.misc,
+            _ => return None,
+        })
+    }
+}
+
+/// Badge color of the part of speech with the dictionary code.
+fn speech_part_color(code: &str) -> Color32 {
+    match code {
+        "с" => Color32::from_rgb(80, 150, 220),
+        "г" => Color32::from_rgb(220, 80, 80),
+        "п" => Color32::from_rgb(90, 180, 90),
+        "н" => Color32::from_rgb(210, 150, 40),
+        _ => Color32::GRAY,
+    }
 }
 
-#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq)]
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Hash, Clone)]
 enum Theme {
     No,
     Preset(String),
+    /// One of the custom themes saved by the user.
+    User(String),
     Custom,
 }
 
@@ -115,32 +432,81 @@ impl From<Option<String>> for Theme {
 impl Theme {
     fn name(&self) -> String {
         match self {
-            Self::No => "Без темы".to_string(),
-            Self::Preset(s) => s.clone(),
-            Self::Custom => "Пользовательская".to_string(),
+            Self::No => tr("Без темы").to_string(),
+            Self::Preset(s) | Self::User(s) => s.clone(),
+            Self::Custom => tr("Пользовательская").to_string(),
         }
     }
 
-    fn mean_theme(&self, custom_theme_text: &str) -> Result<Option<MeanTheme>, Vec<String>> {
-        let splitted;
-        let words = match self {
-            Self::No => return Ok(None),
-            Self::Preset(s) => &MEAN_STR_THEMES.str_themes[s],
-            Self::Custom => {
-                splitted = custom_theme_text
-                    .split_whitespace()
-                    .map(ToString::to_string)
-                    .collect();
-                &splitted
-            }
-        };
+    /// `custom_theme_text` holds the words of the custom and user themes.
+    fn words(&self, custom_theme_text: &str) -> Vec<String> {
+        match self {
+            Self::No => vec![],
+            Self::Preset(s) => MEAN_STR_THEMES.str_themes[s].clone(),
+            Self::User(_) | Self::Custom => custom_theme_text
+                .split_whitespace()
+                .map(ToString::to_string)
+                .collect(),
+        }
+    }
 
-        MeanTheme::from_str(&WORD_COLLECTOR, words)
+    fn mean_theme(&self, custom_theme_text: &str) -> Result<Option<MeanTheme>, Vec<String>> {
+        if *self == Self::No {
+            return Ok(None);
+        }
+        MeanTheme::from_str(&WORD_COLLECTOR, &self.words(custom_theme_text))
             .map(Some)
             .map_err(|v| v.into_iter().cloned().collect())
     }
 }
 
+/// Order of the shown rhymes.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Default)]
+enum ResultOrder {
+    /// Best rhymes first.
+    #[default]
+    Score,
+    /// Most common words first.
+    Popularity,
+}
+
+impl ResultOrder {
+    const ALL: [Self; 2] = [Self::Score, Self::Popularity];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Score => "По оценке",
+            Self::Popularity => "По популярности",
+        }
+    }
+}
+
+/// Rhyme settings shared as a file.
+#[derive(serde::Deserialize, serde::Serialize, Default)]
+#[serde(default)]
+struct SettingsFile {
+    settings: GeneralSettings,
+    remove_parts_of_speech: RemovePartsOfSpeech,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct LastResults {
+    /// Dictionary the results were found with, see [`dictionary_version`].
+    dictionary: usize,
+    query: String,
+    results: Vec<String>,
+    /// Scores of `results`, missing in the files of older versions.
+    #[serde(default)]
+    scores: Vec<f32>,
+}
+
+/// Changes whenever the bundled dictionary does, making saved results outdated.
+/// The user's own words are left out, adding them doesn't outdate anything.
+fn dictionary_version() -> usize {
+    lazy_static::initialize(&WORD_COLLECTOR);
+    custom_words::bundled_count()
+}
+
 impl Default for QuickpoeterApp {
     fn default() -> Self {
         Self {
@@ -151,17 +517,131 @@ impl Default for QuickpoeterApp {
             show_theme: Default::default(),
             show_settings: Default::default(),
             rhyme_output: Ok(vec![]),
+            query_locked: false,
+            query_back: vec![],
+            query_forward: vec![],
             rps: RemovePartsOfSpeech::default(),
+            rps_presets: BTreeMap::new(),
+            baseline: None,
             custom_theme_text: String::new(),
+            user_themes: BTreeMap::new(),
             show_rhymes: 50,
+            search_rhymes: 200,
+            creativity: 0.5,
+            show_homophones: false,
+            show_examples: false,
+            show_score_parts: false,
+            show_changes: false,
+            session_log: SessionLog::default(),
+            results_before: None,
+            show_stress: true,
+            stress_mark: StressMark::default(),
+            read_aloud: true,
+            dictionary_url: "https://ru.wiktionary.org/wiki/{}".to_string(),
+            font_size: 18.0,
+            panel_width: 250.0,
+            drawer_open: true,
+            loose_pass: false,
+            strict_rhyme: false,
+            exclude_same_root: false,
+            merge_yo: true,
+            tolerance: DEFAULT_TOLERANCE,
+            rhyme_mode: RhymeMode::default(),
+            min_syllables: 0,
+            quality_thresholds: [10.0, 25.0],
+            max_syllables: 0,
+            excluded_words: vec![],
+            rhyme_type_filter: None,
+            result_order: ResultOrder::default(),
+            enter_behavior: EnterBehavior::default(),
+            follow_cursor: false,
+            live_search: false,
+            highlight_mode: HighlightMode::default(),
+            alliteration_radius: 2,
+            show_meter: false,
+            reference_text: String::new(),
+            show_reference: false,
+            editing_reference: false,
+            meter: None,
+            language: Language::default(),
+            dark_mode: true,
+            accent: Color32::RED,
+            pending: None,
+            line_rhymes: LineRhymes::default(),
+            preview_settings: false,
+            settings_preview: SettingsPreview::default(),
+            line_endings: None,
+            dictionary_loading: None,
+            dictionary_loaded: false,
+            homophones: vec![],
+            score_parts: HashMap::new(),
+            loose_output: vec![],
+            results_shown: RESULTS_PAGE,
+            searched: None,
+            highlighter: Highlighter::default(),
+            history: editor::History::default(),
+            counter: editor::Counter::default(),
+            query_info: None,
+            query_stress: None,
+            comparison: None,
+            theme_neighbours: None,
+            baseline_comparison: None,
+            sound_alike: None,
+            cursor_line: None,
+            cursor_moved_at: None,
+            query_edited_at: None,
+            rng: 0,
+            settings_file: None,
+            settings_file_error: None,
+            clearing_data: None,
+            autosaved: (String::new(), 0.0),
+            draft_replaced: None,
+            export_error: None,
+            unknown_query: None,
+            found_total: None,
+            new_word: None,
+            inflections: None,
+            theme_filter: String::new(),
+            theme_list_open: false,
+            new_excluded_word: String::new(),
+            preset_name: String::new(),
+            rps_preset_name: String::new(),
+            user_theme_name: String::new(),
+            user_theme_error: None,
+            presets: presets::Presets::new(),
+            custom_words: CustomWords::new(),
+            custom_words_input: None,
+            notes: HashMap::new(),
+            templates: PoemTemplate::defaults(),
+            last_results: None,
+            recent_searches: vec![],
+            collected: vec![],
+            result_style: ResultStyle::default(),
+            restored_results: false,
+            expected_scheme: None,
+            new_template: None,
+            editing_note: None,
             theme: Theme::No,
         }
     }
 }
 
 lazy_static! {
-    static ref WORD_COLLECTOR: WordCollector = WordCollector::default();
+    static ref WORD_COLLECTOR: WordCollector = custom_words::merged(WordCollector::default());
     static ref MEAN_STR_THEMES: MeanStrThemes = MeanStrThemes::default();
+    /// Without the word vectors every theme word is unknown and no theme can be built.
+    static ref THEMES_AVAILABLE: bool = themes_available();
+}
+
+fn themes_available() -> bool {
+    MEAN_STR_THEMES
+        .str_themes
+        .values()
+        .next()
+        .is_some_and(|words| match MeanTheme::from_str(&WORD_COLLECTOR, words) {
+            Ok(_) => true,
+            Err(unknown) => unknown.len() < words.len(),
+        })
 }
 
 impl QuickpoeterApp {
@@ -172,11 +652,81 @@ impl QuickpoeterApp {
 
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
-        if let Some(storage) = cc.storage {
-            return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        #[allow(unused_mut)]
+        let mut app: Self = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+
+        // files are the source of truth for presets on native
+        #[cfg(not(target_arch = "wasm32"))]
+        app.presets.extend(presets::load_dir());
+
+        // the state is saved on a clean exit and every 30 seconds, the draft more often;
+        // a draft unlike the saved text is newer, the state would have caught up otherwise
+        let draft: Option<String> = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, DRAFT_KEY));
+        if let Some(draft) = draft.filter(|d| *d != app.main_text) {
+            app.draft_replaced = Some(std::mem::replace(&mut app.main_text, draft));
+        }
+        app.autosaved.0 = app.main_text.clone();
+        custom_words::merge_on_load(&app.custom_words);
+
+        cc.egui_ctx.set_visuals(match app.dark_mode {
+            true => egui::Visuals::dark(),
+            false => egui::Visuals::light(),
+        });
+
+        app
+    }
+
+    /// Starts loading the dictionary on the first call; `true` once it's loaded.
+    fn poll_dictionary(&mut self) -> bool {
+        if self.dictionary_loaded {
+            return true;
         }
+        // started after the first frame, so on the web the overlay gets painted before the stall
+        let loading = self.dictionary_loading.get_or_insert_with(|| {
+            search::spawn("dictionary", || {
+                lazy_static::initialize(&WORD_COLLECTOR);
+                lazy_static::initialize(&THEMES_AVAILABLE);
+            })
+        });
+        if loading.ready().is_none() {
+            return false;
+        }
+        self.dictionary_loading = None;
+        self.dictionary_loaded = true;
+        self.restore_last_results();
+        true
+    }
 
-        Self::default()
+    fn restore_last_results(&mut self) {
+        match self.last_results.take() {
+            Some(last) if last.dictionary == dictionary_version() => {
+                self.rhyme_word = last.query.clone();
+                let scores = last
+                    .scores
+                    .iter()
+                    .copied()
+                    .chain(std::iter::repeat(f32::NAN));
+                self.rhyme_output = Ok(last
+                    .results
+                    .iter()
+                    .zip(scores)
+                    .filter_map(|(word, score)| {
+                        Some(Rhyme {
+                            word: WORD_COLLECTOR.get_word(word)?,
+                            score,
+                        })
+                    })
+                    .collect());
+                self.restored_results = true;
+                self.last_results = Some(last);
+            }
+            _ => {}
+        }
     }
 }
 
@@ -184,259 +734,3045 @@ impl eframe::App for QuickpoeterApp {
     /// Called by the frame work to save state before shutdown.
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         eframe::set_value(storage, eframe::APP_KEY, self);
+        eframe::set_value(storage, DRAFT_KEY, &self.main_text);
     }
 
     /// Called each time the UI needs repainting, which may be many times per second.
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        i18n::set_language(self.language);
+        speech::set_enabled(self.read_aloud);
+        let loaded = self.poll_dictionary();
+        self.poll_search();
+        self.poll_settings_file();
+        self.research_if_reanalyzed();
+
+        // hidden, for capturing widget info in UI bug reports
+        let toggle_debug = egui::KeyboardShortcut::new(
+            egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+            egui::Key::D,
+        );
+        if ctx.input_mut(|i| i.consume_shortcut(&toggle_debug)) {
+            let debug = !ctx.debug_on_hover();
+            ctx.set_debug_on_hover(debug);
+            ctx.style_mut(|style| {
+                style.debug.show_expand_width = debug;
+                style.debug.show_expand_height = debug;
+                style.debug.show_resize = debug;
+                style.debug.show_interactive_widgets = debug;
+            });
+        }
+
+        let focus_query = ctx.input_mut(|i| {
+            i.consume_shortcut(&egui::KeyboardShortcut::new(
+                egui::Modifiers::COMMAND,
+                egui::Key::F,
+            ))
+        });
+
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 #[cfg(not(target_arch = "wasm32"))] // no File->Quit on web pages!
                 {
                     ui.menu_button("File", |ui| {
                         if ui.button("Quit").clicked() {
-                            _frame.close();
+                            frame.close();
                         }
                     });
                     ui.add_space(16.0);
                 }
 
+                ui.menu_button(tr("Данные"), |ui| {
+                    if ui.button(tr("Свои слова…")).clicked() {
+                        self.custom_words_input = Some(String::new());
+                        ui.close_menu();
+                    }
+                    if ui.button(tr("Очистить все данные…")).clicked() {
+                        self.clearing_data = Some(false);
+                        ui.close_menu();
+                    }
+                });
+                ui.add_space(16.0);
+
+                ui.menu_button(tr("Шаблоны"), |ui| self.show_templates_menu(ui));
+                ui.add_space(16.0);
+
+                ui.menu_button(tr("Редактор"), |ui| {
+                    for behavior in EnterBehavior::ALL {
+                        ui.radio_value(&mut self.enter_behavior, behavior, tr(behavior.name()));
+                    }
+                    ui.checkbox(&mut self.follow_cursor, tr("Рифмы к строке под курсором"));
+                    if ui
+                        .add_enabled(loaded, egui::Button::new(tr("Подобрать для всех строк")))
+                        .clicked()
+                    {
+                        self.find_line_rhymes();
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add_enabled(loaded, egui::Button::new(tr("Экспорт с разбором")))
+                        .on_hover_text(tr("Текст со схемой рифмовки и ударениями строк"))
+                        .clicked()
+                    {
+                        self.export_poem_report();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    for mode in HighlightMode::ALL {
+                        ui.radio_value(&mut self.highlight_mode, mode, tr(mode.name()));
+                    }
+                    if self.highlight_mode == HighlightMode::Alliteration {
+                        ui.add(
+                            Slider::new(&mut self.alliteration_radius, 1..=10)
+                                .text(tr("Радиус аллитераций, слов")),
+                        );
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut self.show_meter, tr("Ударения строк и размер"));
+                    if self.show_meter {
+                        ui.radio_value(&mut self.meter, None, tr("Определять размер сам"));
+                        for meter in Meter::ALL {
+                            ui.radio_value(&mut self.meter, Some(meter), tr(meter.name()));
+                        }
+                    }
+                });
+                ui.add_space(16.0);
+
+                ui.toggle_value(&mut self.show_reference, tr("Справка"))
+                    .on_hover_text(tr(
+                        "Текст рядом со стихотворением: задание или оригинал перевода",
+                    ));
+                ui.add_space(16.0);
+
                 egui::widgets::global_dark_light_mode_buttons(ui);
+                self.dark_mode = ui.visuals().dark_mode;
+                ui.color_edit_button_srgba(&mut self.accent)
+                    .on_hover_text(tr("Цвет выделения"));
+                ui.add_space(16.0);
+
+                ComboBox::from_id_source("language")
+                    .selected_text(self.language.name())
+                    .show_ui(ui, |ui| {
+                        for lang in Language::ALL {
+                            ui.selectable_value(&mut self.language, lang, lang.name());
+                        }
+                    });
             });
         });
 
-        egui::SidePanel::right("rhymes")
-            .min_width(200.0)
-            .show(ctx, |ui| {
-                ui.horizontal(|ui| {
-                    ui.toggle_value(&mut self.show_theme, "Тема");
-                    ui.toggle_value(&mut self.show_settings, "Параметры рифм");
+        let compact = ctx.screen_rect().width() < COMPACT_WIDTH;
+        ctx.style_mut(|style| {
+            let spacing = egui::style::Spacing::default();
+            (style.spacing.interact_size.y, style.spacing.button_padding) = match compact {
+                // fingers are bigger than the pointer
+                true => (TOUCH_SIZE, spacing.button_padding * 2.0),
+                false => (spacing.interact_size.y, spacing.button_padding),
+            };
+        });
+
+        if compact {
+            // a drawer under the text, a side panel would squeeze it
+            egui::TopBottomPanel::bottom("rhymes_drawer")
+                .resizable(self.drawer_open)
+                .default_height(ctx.screen_rect().height() * 0.5)
+                .show(ctx, |ui| {
+                    let arrow = if self.drawer_open { "⏷" } else { "⏶" };
+                    if ui.button(format!("{arrow} {}", tr("Рифмы"))).clicked() {
+                        self.drawer_open = !self.drawer_open;
+                    }
+                    if self.drawer_open {
+                        self.show_rhymes_panel(ctx, ui, loaded, focus_query);
+                    }
+                });
+        } else {
+            // the screen may be smaller than the one the width was saved on
+            let max_width = (ctx.screen_rect().width() * 0.8).max(200.0);
+            let panel = egui::SidePanel::right("rhymes")
+                .min_width(200.0)
+                .max_width(max_width)
+                .default_width(self.panel_width.min(max_width))
+                .show(ctx, |ui| {
+                    self.show_rhymes_panel(ctx, ui, loaded, focus_query)
+                });
+            self.panel_width = panel.response.rect.width();
+        }
+
+        if self.show_reference {
+            egui::SidePanel::left("reference")
+                .default_width(ctx.screen_rect().width() * 0.3)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.strong(tr("Справка"));
+                        ui.toggle_value(&mut self.editing_reference, "✏")
+                            .on_hover_text(tr("Править справку"));
+                    });
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        ui.add(
+                            TextEdit::multiline(&mut self.reference_text)
+                                .interactive(self.editing_reference)
+                                .hint_text(tr(
+                                    "Включите правку и вставьте задание или текст оригинала",
+                                ))
+                                .font(FontId::proportional(self.font_size))
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
                 });
+        }
 
-                if self.show_theme {
-                    self.show_theme_select(ui);
+        egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
+            let counts = self.counter.count(&self.main_text);
+            let mut status = format!(
+                "{}: {}   {}: {}   {}: {}   {}: {}",
+                tr("Символов"),
+                counts.chars,
+                tr("Слов"),
+                counts.words,
+                tr("Строк"),
+                counts.lines,
+                tr("Слогов"),
+                counts.syllables,
+            );
+            if loaded && self.show_meter {
+                if let Some(meter) = self.current_meter() {
+                    status += &format!("   {}: {}", tr("Размер"), tr(meter.name()));
                 }
+            }
+            ui.label(RichText::new(status).small().weak());
+        });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let editor_id = editor::main_text_id();
 
+            if let Some(scheme) = &self.expected_scheme {
+                let mut drop = false;
                 ui.horizontal(|ui| {
-                    let input = TextEdit::singleline(&mut self.rhyme_word)
-                        .font(FontId {
-                            size: 20.0,
-                            family: egui::FontFamily::Monospace,
-                        })
-                        .hint_text("К чему рифму?");
-
-                    let response = ui.add_sized(ui.available_size(), input);
-
-                    if response.lost_focus() && ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
-                        self.rhyme_output = string2word(&WORD_COLLECTOR, &self.rhyme_word)
-                            .and_then(|word| {
-                                find(
-                                    &WORD_COLLECTOR,
-                                    &self.general_settings,
-                                    word,
-                                    self.theme
-                                        .mean_theme(&self.custom_theme_text)
-                                        .map_err(|err| match err.len() {
-                                            0 => "Пустая тема".to_string(),
-                                            _ => format!("Неизвестные слова: {err:?}"),
-                                        })?
-                                        .as_ref(),
-                                    &self.rps.get_list(),
-                                    self.show_rhymes,
-                                )
-                                .map(|r| r.into_iter().map(|r| r.word.src.clone()).collect())
-                            });
-                    }
+                    ui.label(RichText::new(format!("{}: {scheme}", tr("Схема рифмовки"))).weak())
+                        .on_hover_text(tr(
+                            "Подсветка схемы рифмовки отмечает строки, рифмующиеся не по ней",
+                        ));
+                    drop = ui
+                        .small_button("✕")
+                        .on_hover_text(tr("Забыть схему"))
+                        .clicked();
                 });
+                if drop {
+                    self.expected_scheme = None;
+                }
+            }
 
-                match &self.rhyme_output {
-                    Ok(res) => {
-                        egui::ScrollArea::vertical()
-                            .auto_shrink([false; 2])
-                            .show(ui, |ui| ui.label(RichText::new(res.join("\n")).size(18.0)));
-                    }
-                    Err(s) => {
-                        ui.colored_label(Color32::RED, RichText::new(s).size(14.0));
-                    }
-                };
+            // the editor's own undo forgets too much, ours goes first
+            if ui.memory(|m| m.has_focus(editor_id)) {
+                let redo = egui::KeyboardShortcut::new(
+                    egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+                    egui::Key::Z,
+                );
+                let undo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+                if ctx.input_mut(|i| i.consume_shortcut(&redo)) {
+                    self.history.redo(&mut self.main_text);
+                } else if ctx.input_mut(|i| i.consume_shortcut(&undo)) {
+                    self.history.undo(&mut self.main_text);
+                }
+            }
 
-                /*
-                ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
-                    ui.add_space(20.0);
-                    ui.toggle_value(&mut self.show_settings, "Параметры рифм");
-                })
-                */
+            // intercept Enter before the editor turns it into a newline
+            let search_line = loaded
+                && !self.query_locked
+                && ui.memory(|m| m.has_focus(editor_id))
+                && self
+                    .enter_behavior
+                    .search_modifiers()
+                    .is_some_and(|modifiers| {
+                        ctx.input_mut(|i| i.consume_key(modifiers, egui::Key::Enter))
+                    });
+
+            let font_id = FontId {
+                // monospace looks smaller at the same size
+                size: self.font_size + 2.0,
+                family: egui::FontFamily::Monospace,
+            };
+
+            // the highlighting looks words up in the dictionary
+            self.highlighter.set_mode(match loaded {
+                true => self.highlight_mode,
+                false => HighlightMode::No,
             });
+            self.highlighter
+                .set_alliteration_radius(self.alliteration_radius);
+            self.highlighter
+                .set_expected_scheme(self.expected_scheme.as_deref());
+            let highlighter = &mut self.highlighter;
+            let mut layouter = |ui: &Ui, text: &str, wrap_width: f32| {
+                let color = ui.visuals().widgets.inactive.text_color();
+                let mut job = highlighter.highlight(text, font_id.clone(), color);
+                job.wrap.max_width = wrap_width;
+                ui.fonts(|f| f.layout_job(job))
+            };
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.add_sized(
-                ui.available_size(),
-                TextEdit::multiline(&mut self.main_text)
-                    .code_editor()
-                    .font(FontId {
-                        size: 20.0,
-                        family: egui::FontFamily::Monospace,
-                    }),
-            )
+            let show_meter = loaded && self.show_meter;
+            let mut editor_rect = ui.available_rect_before_wrap();
+            let mut gutter = None;
+            if show_meter {
+                let (left, right) =
+                    editor_rect.split_left_right_at_x(editor_rect.left() + GUTTER_WIDTH);
+                gutter = Some(left);
+                editor_rect = right;
+            }
+            let editor = TextEdit::multiline(&mut self.main_text)
+                .id(editor_id)
+                .code_editor()
+                .font(font_id.clone())
+                .layouter(&mut layouter);
+            let output = ui
+                .allocate_ui_at_rect(editor_rect, |ui| {
+                    ui.centered_and_justified(|ui| editor.show(ui)).inner
+                })
+                .inner;
+            if let Some(gutter) = gutter {
+                self.paint_stress_patterns(ui, gutter, &output);
+            }
+            self.history.track(&self.main_text, ctx.input(|i| i.time));
+
+            if search_line {
+                let cursor = editor::cursor(ctx, editor_id).unwrap_or(0);
+                if let Some(word) = editor::line_end_word(&self.main_text, cursor) {
+                    self.swap_query(word.to_string());
+                }
+            }
+
+            if loaded && self.follow_cursor && !self.query_locked {
+                self.follow_cursor_line(ctx);
+            }
         });
 
+        if !loaded {
+            show_loading(ctx);
+            ctx.request_repaint();
+            return;
+        }
+
         self.show_settings_window(ctx);
+        self.show_new_word_window(ctx);
+        self.show_custom_words_window(ctx);
+        self.show_note_window(ctx);
+        self.show_new_template_window(ctx);
+        self.show_comparison_window(ctx);
+        self.show_baseline_window(ctx);
+        self.show_sound_alike_window(ctx);
+        self.show_line_rhymes_window(ctx);
+
+        let previewing =
+            self.show_settings && self.preview_settings && self.settings_preview.is_running();
+        if self.pending.is_some()
+            || self.settings_file.is_some()
+            || previewing
+            || self.line_rhymes.is_running()
+            || running(&self.comparison)
+            || self
+                .theme_neighbours
+                .as_ref()
+                .is_some_and(|(_, p)| p.ready().is_none())
+            || running(&self.baseline_comparison)
+            || running(&self.sound_alike)
+        {
+            ctx.request_repaint();
+        }
+        self.show_clear_data_window(ctx, frame);
+        self.autosave(ctx, frame);
+        self.show_draft_toast(ctx);
+        self.show_export_error(ctx);
     }
 }
 
-impl QuickpoeterApp {
-    fn show_settings_window(&mut self, ctx: &egui::Context) {
-        egui::Window::new("Параметры подбора рифмы").open(&mut self.show_settings).show(ctx, |ui| {
+/// Storage key of the main text saved between the saves of the whole state.
+const DRAFT_KEY: &str = "draft";
 
-            macro_rules! default_or {
-                ($default: expr) => {
-                    $default
-                };
-                ($default: expr, $some: expr) => {
-                    $some
-                };
-            }
+/// Seconds between the autosaves of the edited text.
+const AUTOSAVE_INTERVAL: f64 = 5.0;
 
-            ui.add(
-                Slider::new(&mut self.show_rhymes, 1..=500)
-                    .text("Количество отображаемых рифм")
-            );
+/// Screens narrower than this, phones, get the compact layout.
+const COMPACT_WIDTH: f32 = 600.0;
 
-            if ui.button("Сбросить").clicked() {
-                self.general_settings = GeneralSettings::default();
-            }
+/// Where the master strictness slider starts, in percent.
+const DEFAULT_TOLERANCE: f32 = 50.0;
 
-            ui.checkbox(&mut self.general_settings.stresses.indexation, "Индексация гласных");
+/// Height of the widgets in the compact layout, to be hit by a finger.
+const TOUCH_SIZE: f32 = 32.0;
 
-            egui::ScrollArea::vertical()
-                .auto_shrink([false; 2])
-                .show(ui, |ui| {
-                    macro_rules! setting {
-                        ($block: expr => {$($name: expr, $mut_ref: expr, $max: expr $(, $min: expr)?;)*}) => {
-                            ui.collapsing($block, |ui| {
-                                $(
-                                    ui.add(
-                                        Slider::new(&mut $mut_ref, default_or!(0.0$(, $min)? )..=$max)
-                                            .clamp_to_range(false)
-                                            .text($name),
-                                    );
-                                )*
-                            })
-                        };
-                    }
+/// Width of the stress patterns beside the main text.
+const GUTTER_WIDTH: f32 = 160.0;
 
-                    setting!("Веса" => {
-                        "Тематика", self.general_settings.meaning.weight, 5000.0;
-                        "Популярность слова", self.general_settings.popularity.weight, 1e-5;
-                        "Ударения", self.general_settings.stresses.weight, 200.0;
-                        "Структура", self.general_settings.consonant_structure.weight, 10.0;
-                        "Аллитерации", self.general_settings.alliteration.weight, 10.0;
-                    });
+/// Overlay shown while the dictionary is loading.
+fn show_loading(ctx: &egui::Context) {
+    let time = ctx.input(|i| i.time);
+    egui::Window::new("loading")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(tr("Загрузка словаря…"));
+            // the loading can't tell how far it is, the bar just runs
+            ui.add(egui::ProgressBar::new((time * 0.5).fract() as f32).desired_width(200.0));
+        });
+}
 
-                    setting!("Тематика" => {
-                        "Степень", self.general_settings.meaning.pow, 5.0;
-                        "Степень для одного слова", self.general_settings.meaning.single_pow, 5.0;
-                        "Множитель для одного слова", self.general_settings.meaning.single_weight, 1.0;
-                        "Вес", self.general_settings.meaning.weight, 5000.0;
-                    });
+/// The score and, if known, what it is made of.
+fn score_tooltip(score: f32, parts: Option<&[f32; 5]>) -> Option<String> {
+    if score.is_nan() {
+        return None;
+    }
+    let mut tooltip = format!("{}: {score:.2}", tr("Оценка"));
+    if let Some(parts) = parts {
+        for (name, part) in search::SCORE_PARTS.iter().zip(parts) {
+            match part.is_nan() {
+                true => tooltip += &format!("\n{}: —", tr(name)),
+                false => tooltip += &format!("\n{}: {part:.2}", tr(name)),
+            }
+        }
+        let rest = score - parts.iter().filter(|p| !p.is_nan()).sum::<f32>();
+        tooltip += &format!("\n{}: {rest:.2}", tr("Прочее"));
+    }
+    Some(tooltip)
+}
 
-                    setting!("Разное" => {
-                        "Близкая длина", self.general_settings.misc.length_diff_fine, 3.0;
-                        "Совпадающие гласные/согласные в конце", self.general_settings.misc.same_cons_end, 3.0;
-                    });
+/// Editor of the words excluded from the results, `new_word` being typed in.
+fn excluded_words_editor(ui: &mut Ui, words: &mut Vec<String>, new_word: &mut String) {
+    let mut removed = None;
+    ui.horizontal_wrapped(|ui| {
+        for (i, word) in words.iter().enumerate() {
+            if ui
+                .button(format!("{word} ✕"))
+                .on_hover_text(tr("Вернуть в результаты"))
+                .clicked()
+            {
+                removed = Some(i);
+            }
+        }
+    });
+    if let Some(i) = removed {
+        words.remove(i);
+    }
 
-                    setting!("Популярность слова" => {
-                        "Вес", self.general_settings.popularity.weight, 1e-5;
-                        "Степень", self.general_settings.popularity.pow, 5.0;
-                    });
+    ui.horizontal(|ui| {
+        let response = ui.add(
+            TextEdit::singleline(new_word)
+                .hint_text(tr("Слово"))
+                .desired_width(120.0),
+        );
+        let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        let word = new_word.trim().to_lowercase();
+        if (ui.button(tr("Исключить")).clicked() || entered) && !word.is_empty() {
+            if !words.contains(&word) {
+                words.push(word);
+            }
+            new_word.clear();
+        }
+    });
+}
 
-                    setting!("Ударения" => {
-                        "Строгие ударения", self.general_settings.stresses.k_strict_stress, 40.0;
-                        "Нестрогие ударения", self.general_settings.stresses.k_not_strict_stress, 7.0;
-                        "Штраф за плохой ритм", self.general_settings.stresses.bad_rythm, 100.0;
-                        "Сдвиг веса сравнения гласных", self.general_settings.stresses.shift_syll_ending, 3.0;
-                        "Степень веса сравнения гласных", self.general_settings.stresses.pow_syll_ending, 3.0;
-                        "Ассимптотика метрики", self.general_settings.stresses.asympt, 3.0;
-                        "Сдвиг ассимптотики метрики", self.general_settings.stresses.asympt_shift, 2.0;
-                        "Вес", self.general_settings.stresses.weight, 200.0;
-                    });
+/// Buttons setting the excluded parts of speech at once, and saving them under `new_name`.
+fn rps_presets_editor(
+    ui: &mut Ui,
+    rps: &mut RemovePartsOfSpeech,
+    presets: &mut BTreeMap<String, RemovePartsOfSpeech>,
+    new_name: &mut String,
+) {
+    ui.horizontal_wrapped(|ui| {
+        for (name, preset) in RemovePartsOfSpeech::builtin_presets() {
+            if ui.selectable_label(*rps == preset, tr(name)).clicked() {
+                *rps = preset;
+            }
+        }
+    });
 
-                    setting!("Длина искомой рифмы" => {
-                        "Идеальная длина", self.general_settings.unsymmetrical.optimal_length, 15.0;
-                        "Вес штрафа меньших", self.general_settings.unsymmetrical.less_w, 0.5;
-                        "Степень штрафа меньших", self.general_settings.unsymmetrical.less_pow, 1.1;
-                        "Вес штрафа больших", self.general_settings.unsymmetrical.more_w, 0.5;
-                        "Степень штрафа больших", self.general_settings.unsymmetrical.more_pow, 1.1;
-                    });
+    let mut removed = None;
+    ui.horizontal_wrapped(|ui| {
+        for (name, preset) in presets.iter() {
+            if ui.selectable_label(rps == preset, name).clicked() {
+                *rps = preset.clone();
+            }
+            if ui.small_button("🗑").on_hover_text(tr("Удалить")).clicked() {
+                removed = Some(name.clone());
+            }
+        }
+    });
+    if let Some(name) = removed {
+        presets.remove(&name);
+    }
 
-                    setting!("Штрафы за совпадающие части речи" => {
-                        "Глаголы", self.general_settings.same_speech_part.verb, 2.0;
-                        "Прилагательные", self.general_settings.same_speech_part.adj, 1.0;
-                        "Существительные", self.general_settings.same_speech_part.noun, 1.0;
-                        "Наречия", self.general_settings.same_speech_part.adv, 1.0;
-                    });
+    ui.horizontal(|ui| {
+        ui.add(
+            TextEdit::singleline(new_name)
+                .hint_text(tr("Название пресета"))
+                .desired_width(150.0),
+        );
+        let name = new_name.trim();
+        if ui
+            .add_enabled(!name.is_empty(), egui::Button::new(tr("Сохранить")))
+            .clicked()
+        {
+            presets.insert(name.to_string(), rps.clone());
+            new_name.clear();
+        }
+    });
+    ui.separator();
+}
 
-                    setting!("Структура" => {
-                        "Степень разности длин слогов", self.general_settings.consonant_structure.pow, 5.0;
-                        "Сдвиг множителя сравнения с конца", self.general_settings.consonant_structure.shift_syll_ending, 5.0;
-                        "Степень множителя сравнения с конца", self.general_settings.consonant_structure.pow_syll_ending, 5.0;
-                        "Ассимптотика метрики", self.general_settings.consonant_structure.asympt, 3.0;
-                        "Сдвиг ассимптотики", self.general_settings.consonant_structure.asympt_shift, 5.0;
-                        "Вес", self.general_settings.consonant_structure.weight, 10.0;
-                    });
+/// Why the settings can't rank the rhymes usefully, if so. The sliders don't
+/// clamp the values, so anything can be dragged in.
+fn settings_warning(settings: &GeneralSettings) -> Option<&'static str> {
+    // with the upper bounds of their sliders
+    let weights = [
+        (settings.meaning.weight, 5000.0),
+        (settings.popularity.weight, 1e-5),
+        (settings.stresses.weight, 200.0),
+        (settings.consonant_structure.weight, 10.0),
+        (settings.alliteration.weight, 10.0),
+    ];
+    let [_, _, sound @ ..] = weights;
 
-                    setting!("Аллитерации" => {
-                        "Сдвиг расстояния в слове между буквами", self.general_settings.alliteration.shift_coord, 5.0;
-                        "Степень расстояния в слове между буквами", self.general_settings.alliteration.pow_coord_delta, 5.0;
-                        "Сдвиг важности согласных в концовке", self.general_settings.alliteration.shift_syll_ending, 5.0;
-                        "Степень важности согласных в концовке", self.general_settings.alliteration.pow_syll_ending, 3.0, -3.0;
-                        "Штраф за дополнительные звуки", self.general_settings.alliteration.permutations, 50.0;
-                        "Ассимптотика метрики", self.general_settings.alliteration.asympt, 3.0;
-                        "Сдвиг ассимптотики", self.general_settings.alliteration.asympt_shift, 5.0;
-                        "Вес", self.general_settings.alliteration.weight, 10.0;
-                    });
+    if weights.iter().any(|(w, _)| !w.is_finite()) {
+        Some("Вес задан не числом, результаты непредсказуемы")
+    } else if weights.iter().any(|&(w, _)| w < 0.0) {
+        Some("Отрицательный вес поднимает наверх худшие рифмы")
+    } else if weights.iter().all(|&(w, _)| w == 0.0) {
+        Some("Все веса нулевые, рифмы никак не ранжируются")
+    } else if sound.iter().all(|&(w, _)| w == 0.0) {
+        Some("Звучание не учитывается, результаты не будут рифмами")
+    } else if weights.iter().any(|&(w, max)| w > max * 100.0) {
+        Some("Один из весов намного больше обычного, остальные почти не влияют")
+    } else {
+        None
+    }
+}
 
-                    ui.collapsing("Исключить части речи", |ui| {
-                        ui.checkbox(&mut self.rps.noun, "Существительные");
-                        ui.checkbox(&mut self.rps.adj, "Прилагательные");
-                        ui.checkbox(&mut self.rps.pronoun, "Местоимения");
-                        ui.checkbox(&mut self.rps.pronoun_adj, "Местоимения-прилагательные");
-                        ui.checkbox(&mut self.rps.verb, "Глаголы");
-                        ui.checkbox(&mut self.rps.adv, "Наречия");
-                        ui.checkbox(&mut self.rps.num, "Числительные");
-                        ui.checkbox(&mut self.rps.num_adj, "Счётные прилагательные");
-                        ui.checkbox(&mut self.rps.linking, "Вводные");
-                        ui.checkbox(&mut self.rps.citoslovce, "Междометия");
-                        ui.checkbox(&mut self.rps.pred, "Предикативы");
-                        ui.checkbox(&mut self.rps.prep, "Предлоги");
-                        ui.checkbox(&mut self.rps.conj, "Союзы");
-                        ui.checkbox(&mut self.rps.compare, "Сравнительные степени");
-                        ui.checkbox(&mut self.rps.part, "Частицы");
-                        ui.checkbox(&mut self.rps.misc, "Прочее (фразеологизмы, устаревшие…)");
-                    });
+/// Picker of the same part of speech penalties, as (verb, adj, noun, adv).
+fn speech_part_presets(ui: &mut Ui, settings: &mut GeneralSettings) {
+    let penalties = &mut settings.same_speech_part;
+    let default = GeneralSettings::default().same_speech_part;
+    let presets = [
+        (
+            tr("По умолчанию"),
+            [default.verb, default.adj, default.noun, default.adv],
+        ),
+        (tr("Нейтрально"), [0.0, 0.0, 0.0, 0.0]),
+        (tr("Избегать однокоренных глаголов"), [2.0, 0.0, 0.0, 0.0]),
+        (tr("Избегать одинаковых частей речи"), [2.0, 1.0, 1.0, 1.0]),
+    ];
+
+    let current = [penalties.verb, penalties.adj, penalties.noun, penalties.adv];
+    let selected = presets
+        .iter()
+        .find(|(_, values)| *values == current)
+        .map_or(tr("Свои значения"), |(name, _)| name);
+
+    ComboBox::from_label(tr("Части речи в рифме"))
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            for (name, [verb, adj, noun, adv]) in presets {
+                if ui.selectable_label(selected == name, name).clicked() {
+                    penalties.verb = verb;
+                    penalties.adj = adj;
+                    penalties.noun = noun;
+                    penalties.adv = adv;
                 }
-            )
+            }
         });
-    }
+}
 
-    fn show_theme_select(&mut self, ui: &mut Ui) {
-        ui.add_space(10.0);
-        ComboBox::from_label("Встроенная тема")
-            .selected_text(self.theme.name())
-            .show_ui(ui, |ui| {
-                ui.selectable_value(&mut self.theme, Theme::No, "Без темы");
+/// How many recent searches are remembered.
+const RECENT_COUNT: usize = 20;
 
-                for s in MEAN_STR_THEMES.str_themes.keys() {
-                    ui.selectable_value(&mut self.theme, Theme::Preset(s.to_string()), s);
-                }
-            });
-        ui.selectable_value(&mut self.theme, Theme::Custom, "Пользовательская");
+/// Seconds the caret has to stay on a line before its rhymes are searched.
+const FOLLOW_CURSOR_DELAY: f64 = 0.4;
+
+/// Seconds after the last keystroke in the query before the live search runs.
+const LIVE_SEARCH_DELAY: f64 = 0.3;
+
+/// How many top results get an example.
+const EXAMPLES_COUNT: usize = 5;
+
+/// Results shown at once, laying out hundreds of them is slow.
+const RESULTS_PAGE: usize = 50;
+
+/// Naive couplet ending with the query and the rhyme, just to imagine the usage.
+fn example_lines(query: &str, rhyme: &str, variant: usize) -> String {
+    match variant % 3 {
+        0 => format!("…и снова {query},\n…и снова {rhyme}"),
+        1 => format!("…{query} — …\n…— {rhyme}"),
+        _ => format!("…как {query},\n…как {rhyme}"),
+    }
+}
+
+/// Heading of the rhymes with the score, by the upper scores of excellent and good ones.
+fn rhyme_quality(score: f32, thresholds: [f32; 2]) -> &'static str {
+    if score <= thresholds[0] {
+        "Отличные"
+    } else if score <= thresholds[1] {
+        "Хорошие"
+    } else {
+        "Приемлемые"
+    }
+}
+
+/// A line of the results list. All of them are equally high, so that only the
+/// visible ones get laid out.
+enum ResultLine<'a> {
+    Heading(&'static str),
+    Homophone(&'a str),
+    Rhyme(&'a Rhyme),
+    /// Example for the rhyme with the word, the index choosing the variant.
+    Example(usize, &'a str),
+    /// Button showing more of the hidden results.
+    More(usize),
+    Loose(&'a str),
+    /// Result of the previous search of the word, missing now.
+    Dropped(&'a str),
+}
+
+impl ResultLine<'_> {
+    /// A found word, as opposed to the lines around them.
+    fn is_word(&self) -> bool {
+        matches!(self, Self::Homophone(_) | Self::Rhyme(_) | Self::Loose(_))
+    }
+}
+
+/// Results of an earlier search, indexed once to compare the new ones with.
+struct ResultsBefore {
+    /// Word → its place.
+    places: HashMap<String, usize>,
+    /// Words not found anymore, in their old order.
+    dropped: Vec<String>,
+}
+
+impl ResultsBefore {
+    fn new(before: Vec<String>, res: &[Rhyme]) -> Self {
+        let found: HashSet<&str> = res.iter().map(|r| r.word.src.as_str()).collect();
+        let dropped = before
+            .iter()
+            .filter(|w| !found.contains(w.as_str()))
+            .cloned()
+            .collect();
+        let places = before
+            .into_iter()
+            .enumerate()
+            .map(|(i, w)| (w, i))
+            .collect();
+        Self { places, dropped }
+    }
+}
+
+/// Mark of a word now at the place `now` that was at `before`: new, or how
+/// many places up or down it went; `None` if it stayed.
+fn place_change(before: Option<usize>, now: usize) -> Option<(String, Color32)> {
+    match before {
+        None => Some(("✚".to_string(), Color32::from_rgb(80, 150, 220))),
+        Some(was) if was > now => Some((format!("▲{}", was - now), Color32::from_rgb(90, 180, 90))),
+        Some(was) if was < now => Some((format!("▼{}", now - was), Color32::from_rgb(220, 80, 80))),
+        Some(_) => None,
+    }
+}
+
+/// Width of a column of results, more columns fit into a wide panel.
+const RESULT_COLUMN_WIDTH: f32 = 300.0;
+
+fn result_columns(width: f32) -> usize {
+    (width / RESULT_COLUMN_WIDTH).clamp(1.0, 3.0) as usize
+}
+
+/// Lines of the results in rows of up to `columns` words; headings, examples
+/// and buttons take a whole row.
+fn pack_rows<'a>(lines: Vec<ResultLine<'a>>, columns: usize) -> Vec<Vec<ResultLine<'a>>> {
+    let mut rows: Vec<Vec<ResultLine<'a>>> = vec![];
+    for line in lines {
+        match rows.last_mut() {
+            Some(row) if line.is_word() && row[0].is_word() && row.len() < columns => {
+                row.push(line)
+            }
+            _ => rows.push(vec![line]),
+        }
+    }
+    rows
+}
+
+enum ResultAction {
+    Search(String),
+    /// Exclude the part of speech with the code from the results.
+    ExcludeSpeechPart(String),
+    Insert(String),
+    EditNote(String),
+    /// Add the word to the collected ones or remove it from them.
+    ToggleCollected(String),
+    /// Look the word up in the dictionary of [`QuickpoeterApp::dictionary_url`].
+    Define(String),
+}
+
+/// How common the word is, relative to the most common of the results.
+fn popularity_bar(ui: &mut Ui, popularity: f32, max: f32) {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(20.0, 6.0), egui::Sense::hover());
+    let share = if max > 0.0 {
+        (popularity / max).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let painter = ui.painter();
+    painter.rect_filled(rect, 2.0, ui.visuals().faint_bg_color);
+    let mut filled = rect;
+    filled.set_width(rect.width() * share);
+    painter.rect_filled(filled, 2.0, ui.visuals().weak_text_color());
+    response.on_hover_text(format!("{}: {popularity:.2}", tr("Популярность")));
+}
+
+/// Shows a found word; its context menu offers to insert it or one of its forms.
+fn result_row(
+    ui: &mut Ui,
+    word: &str,
+    text: RichText,
+    inflections: &mut Option<(String, Vec<String>)>,
+    notes: &HashMap<String, String>,
+    collected: &[String],
+    score: Option<String>,
+) -> Option<ResultAction> {
+    let mut action = None;
+
+    let response = ui
+        .horizontal(|ui| {
+            if let Some(t) = RhymeType::of(&WORD_COLLECTOR, word) {
+                ui.label(RichText::new(t.badge()).small().weak())
+                    .on_hover_text(tr(t.name()));
+            }
+            if let Some(known) = WORD_COLLECTOR.get_word(word) {
+                let code = known.speech_part.as_str();
+                let badge =
+                    egui::Label::new(RichText::new(code).small().color(speech_part_color(code)))
+                        .sense(egui::Sense::click());
+                if ui
+                    .add(badge)
+                    .on_hover_text(tr("Исключить эту часть речи и повторить поиск"))
+                    .clicked()
+                {
+                    action = Some(ResultAction::ExcludeSpeechPart(code.to_string()));
+                }
+            }
+            let hint = tr("Вставить в текст, правый клик — другие действия");
+            let response = ui.link(text).on_hover_text(match score {
+                Some(score) => format!("{score}\n\n{hint}"),
+                None => hint.to_string(),
+            });
+            if ui
+                .small_button("🔍")
+                .on_hover_text(tr("Искать рифмы к этому слову"))
+                .clicked()
+            {
+                action = Some(ResultAction::Search(word.to_string()));
+            }
+            if ui
+                .small_button("📋")
+                .on_hover_text(tr("Копировать"))
+                .clicked()
+            {
+                ui.output_mut(|o| o.copied_text = word.to_string());
+            }
+            let (star, hint) = match collected.iter().any(|w| w == word) {
+                true => ("★", tr("Убрать из копилки")),
+                false => ("☆", tr("Отложить в копилку")),
+            };
+            if ui.small_button(star).on_hover_text(hint).clicked() {
+                action = Some(ResultAction::ToggleCollected(word.to_string()));
+            }
+            if let Some(note) = notes.get(word) {
+                ui.label("📝").on_hover_text(note);
+            }
+            response
+        })
+        .inner;
+    if response.clicked() {
+        action = Some(ResultAction::Insert(word.to_string()));
+    }
+
+    response.context_menu(|ui| {
+        if ui.button(tr("Искать рифмы к слову")).clicked() {
+            action = Some(ResultAction::Search(word.to_string()));
+            ui.close_menu();
+        }
+
+        ui.menu_button(tr("Вставить в другой форме"), |ui| {
+            if !matches!(inflections, Some((w, _)) if w == word) {
+                *inflections = Some((word.to_string(), inflection::forms(&WORD_COLLECTOR, word)));
+            }
+            let (_, forms) = inflections.as_ref().expect("just filled");
+
+            if forms.is_empty() {
+                ui.label(tr("Других форм не найдено"));
+            }
+            for form in forms {
+                if ui.button(form).clicked() {
+                    action = Some(ResultAction::Insert(form.clone()));
+                    ui.close_menu();
+                }
+            }
+        });
+
+        if speech::enabled() && ui.button(tr("Прочитать вслух")).clicked() {
+            speech::say(&prosody::accented(&WORD_COLLECTOR, word, StressMark::Acute));
+            ui.close_menu();
+        }
+
+        if ui.button(tr("Значение слова")).clicked() {
+            action = Some(ResultAction::Define(word.to_string()));
+            ui.close_menu();
+        }
+
+        if ui.button(tr("Заметка…")).clicked() {
+            action = Some(ResultAction::EditNote(word.to_string()));
+            ui.close_menu();
+        }
+    });
+
+    action
+}
+
+/// Whether a background job is still at work.
+fn running<T: Send>(promise: &Option<Promise<T>>) -> bool {
+    promise.as_ref().is_some_and(|p| p.ready().is_none())
+}
+
+/// Scrollable list of words, returns the clicked one.
+fn word_links(ui: &mut Ui, words: &[String], size: f32) -> Option<String> {
+    let mut clicked = None;
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for word in words {
+            if ui
+                .link(RichText::new(word).size(size))
+                .on_hover_text(tr("Вставить в текст"))
+                .clicked()
+            {
+                clicked = Some(word.clone());
+            }
+        }
+    });
+    clicked
+}
+
+impl QuickpoeterApp {
+    fn show_results(&mut self, ctx: &egui::Context, ui: &mut Ui) {
+        if let Some(query) = self.searched.as_ref().map(|q| q.word.clone()) {
+            ui.horizontal(|ui| {
+                if let Some(note) = self.notes.get(&query) {
+                    ui.label(RichText::new(note).italics().weak());
+                }
+                if ui
+                    .small_button("📝")
+                    .on_hover_text(tr("Заметка к запросу"))
+                    .clicked()
+                {
+                    self.edit_note(query);
+                }
+            });
+        }
+
+        if !self.rhyme_word.trim().is_empty() {
+            ui.horizontal(|ui| {
+                if ui
+                    .button(tr("Сравнить метрики"))
+                    .on_hover_text(tr(
+                        "Ранжировать рифмы только по звучанию, только по смыслу и вместе",
+                    ))
+                    .clicked()
+                {
+                    self.compare_metrics();
+                }
+                if ui
+                    .button(tr("Созвучия"))
+                    .on_hover_text(tr("Слова с похожими согласными, без учёта ударений и темы"))
+                    .clicked()
+                {
+                    self.find_sound_alike();
+                }
+                if self.baseline.is_some()
+                    && ui
+                        .button(tr("Сравнить с эталоном"))
+                        .on_hover_text(tr("Ранжировать рифмы эталонными параметрами и текущими"))
+                        .clicked()
+                {
+                    self.compare_with_baseline();
+                }
+            });
+        }
+
+        match &self.pending {
+            Some(pending) if pending.preview_shown => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(RichText::new(tr("Уточняется…")).italics().weak());
+                });
+            }
+            // the spinner is next to the input, old results would only mislead
+            Some(_) => return,
+            None => {}
+        }
+
+        if self.restored_results {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(tr("Старые результаты")).italics().weak())
+                    .on_hover_text(tr("Найдены в прошлый раз, настройки могли измениться"));
+                if ui.small_button(tr("Обновить")).clicked() {
+                    self.search();
+                }
+            });
+        }
+
+        if self.results_stale() {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(tr("Результаты устарели")).italics().weak());
+                if ui.small_button(tr("Обновить")).clicked() {
+                    self.search();
+                }
+            });
+        }
+
+        let mut action = None;
+        let mut surprise = false;
+        match &self.rhyme_output {
+            Ok(res) if res.is_empty() && self.homophones.is_empty() => {
+                // the spinner by the query says enough while searching
+                let hint = match (&self.searched, &self.pending) {
+                    (_, Some(_)) => None,
+                    (None, None) => Some(tr("Введите слово, чтобы подобрать к нему рифмы")),
+                    (Some(_), None) => Some(tr("Ничего не найдено")),
+                };
+                if let Some(hint) = hint {
+                    ui.add_space(20.0);
+                    ui.vertical_centered(|ui| ui.label(RichText::new(hint).italics().weak()));
+                }
+            }
+            Ok(res) => {
+                ComboBox::from_label(tr("Тип рифмы"))
+                    .selected_text(self.rhyme_type_filter.map_or(tr("Любой"), |t| tr(t.name())))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.rhyme_type_filter, None, tr("Любой"));
+                        for t in RhymeType::ALL {
+                            ui.selectable_value(&mut self.rhyme_type_filter, Some(t), tr(t.name()));
+                        }
+                    });
+                ComboBox::from_label(tr("Порядок"))
+                    .selected_text(tr(self.result_order.name()))
+                    .show_ui(ui, |ui| {
+                        for order in ResultOrder::ALL {
+                            ui.selectable_value(&mut self.result_order, order, tr(order.name()));
+                        }
+                    });
+
+                if let Some(searched) = &self.searched {
+                    let total = self.found_total.unwrap_or(res.len());
+                    let mut found = format!("{}: {total}", tr("Найдено"));
+                    if total > searched.show_rhymes as usize {
+                        found += &format!(" ({} {})", tr("показаны первые"), searched.show_rhymes);
+                    }
+                    ui.label(RichText::new(found).weak());
+                }
+
+                let words: Vec<_> = res.iter().map(|r| r.word.src.as_str()).collect();
+                let show_stress = self.show_stress;
+                let size = self.font_size;
+                // egui draws the accent as a glyph of its own, if the font has one at all
+                let has_accent = ui.fonts(|f| f.has_glyph(&FontId::proportional(size), '\u{301}'));
+                let mark = self.stress_mark.resolve(has_accent);
+                let label = |word: &str| match show_stress {
+                    true => prosody::accented(&WORD_COLLECTOR, word, mark),
+                    false => word.to_string(),
+                };
+
+                if !res.is_empty() {
+                    ui.horizontal(|ui| {
+                        let formatted = || export::format_results(&words, self.result_style);
+                        if ui.small_button(tr("Копировать все")).clicked() {
+                            ui.output_mut(|o| o.copied_text = formatted());
+                        }
+                        if ui
+                            .small_button(tr("Экспорт"))
+                            .on_hover_text(tr("Сохранить результаты в файл"))
+                            .clicked()
+                        {
+                            let query = self.searched.as_ref().map_or("", |q| &q.word);
+                            self.export_error = export::save_text(
+                                &format!("{query}.txt"),
+                                &format!("{query}\n\n{}\n", formatted()),
+                            )
+                            .err();
+                        }
+                        ComboBox::from_id_source("result_style")
+                            .selected_text(tr(self.result_style.name()))
+                            .show_ui(ui, |ui| {
+                                for style in ResultStyle::ALL {
+                                    ui.selectable_value(
+                                        &mut self.result_style,
+                                        style,
+                                        tr(style.name()),
+                                    );
+                                }
+                            });
+                    });
+                    ui.horizontal(|ui| {
+                        surprise = ui
+                            .button(tr("🎲 Удиви меня"))
+                            .on_hover_text(tr(
+                                "Вставить неожиданную рифму: пореже и подальше от начала списка",
+                            ))
+                            .clicked();
+                        ui.add(
+                            Slider::new(&mut self.creativity, 0.0..=1.0)
+                                .show_value(false)
+                                .text(tr("Креативность")),
+                        )
+                        .on_hover_text(tr("Как далеко вниз по списку заходить"));
+                    });
+                }
+
+                let mut lines = vec![];
+                if !self.homophones.is_empty() {
+                    lines.push(ResultLine::Heading(tr("Созвучные слова")));
+                    lines.extend(self.homophones.iter().map(|w| ResultLine::Homophone(w)));
+                }
+
+                let shown = self.shown_rhymes(res);
+                let by_popularity = self.result_order == ResultOrder::Popularity;
+                let mut quality = None;
+                for (i, rhyme) in shown.iter().take(self.results_shown).enumerate() {
+                    let q = rhyme_quality(rhyme.score, self.quality_thresholds);
+                    // mixed up by popularity, the qualities would repeat on every line
+                    if !by_popularity && quality != Some(q) {
+                        lines.push(ResultLine::Heading(tr(q)));
+                        quality = Some(q);
+                    }
+                    lines.push(ResultLine::Rhyme(rhyme));
+                    if self.show_examples && i < EXAMPLES_COUNT {
+                        lines.push(ResultLine::Example(i, &rhyme.word.src));
+                    }
+                }
+                let hidden = shown.len().saturating_sub(self.results_shown);
+                if hidden > 0 {
+                    lines.push(ResultLine::More(hidden));
+                }
+
+                if !self.loose_output.is_empty() {
+                    lines.push(ResultLine::Heading(tr("Менее точные")));
+                    lines.extend(self.loose_output.iter().map(|w| ResultLine::Loose(w)));
+                }
+
+                let before = self.results_before.as_ref().filter(|_| self.show_changes);
+                let changes: HashMap<&str, (String, Color32)> =
+                    before.map_or_else(HashMap::new, |b| {
+                        res.iter()
+                            .enumerate()
+                            .filter_map(|(i, r)| {
+                                let word = r.word.src.as_str();
+                                Some((word, place_change(b.places.get(word).copied(), i)?))
+                            })
+                            .collect()
+                    });
+                if let Some(before) = before.filter(|b| !b.dropped.is_empty()) {
+                    lines.push(ResultLine::Heading(tr("Выпали из результатов")));
+                    lines.extend(before.dropped.iter().map(|w| ResultLine::Dropped(w)));
+                }
+
+                let query = self.searched.as_ref().map_or("", |q| &q.word);
+                let max_popularity = res.iter().map(|r| r.word.popularity).fold(0.0, f32::max);
+                let row_height = ui
+                    .fonts(|f| f.row_height(&FontId::proportional(size)))
+                    .max(ui.spacing().interact_size.y);
+                let columns = result_columns(ui.available_width());
+                let rows = pack_rows(lines, columns);
+                let mut show_line = |ui: &mut Ui, line: &ResultLine<'_>| {
+                    let (word, text, score) = match *line {
+                        ResultLine::Heading(heading) => {
+                            ui.label(RichText::new(heading).italics().weak());
+                            return;
+                        }
+                        ResultLine::Example(i, word) => {
+                            let example = example_lines(query, word, i).replace('\n', " / ");
+                            ui.label(RichText::new(example).italics().weak());
+                            return;
+                        }
+                        ResultLine::Dropped(word) => {
+                            ui.label(RichText::new(word).size(size).weak().strikethrough());
+                            return;
+                        }
+                        ResultLine::More(hidden) => {
+                            if ui
+                                .button(format!("{} ({hidden})", tr("Показать ещё")))
+                                .clicked()
+                            {
+                                self.results_shown += RESULTS_PAGE;
+                            }
+                            return;
+                        }
+                        ResultLine::Homophone(word) => (
+                            word,
+                            RichText::new(label(word))
+                                .size(size)
+                                .color(Color32::from_rgb(200, 140, 40)),
+                            None,
+                        ),
+                        ResultLine::Rhyme(rhyme) => {
+                            let word = rhyme.word.src.as_str();
+                            if let Some((change, color)) = changes.get(word) {
+                                ui.label(RichText::new(change).small().color(*color))
+                                    .on_hover_text(tr("Изменение места с прошлого поиска"));
+                            }
+                            (
+                                word,
+                                RichText::new(label(word)).size(size),
+                                score_tooltip(rhyme.score, self.score_parts.get(word)),
+                            )
+                        }
+                        ResultLine::Loose(word) => {
+                            (word, RichText::new(label(word)).size(size).weak(), None)
+                        }
+                    };
+                    if let Some(known) = WORD_COLLECTOR.get_word(word) {
+                        popularity_bar(ui, known.popularity, max_popularity);
+                    }
+                    if let Some(a) = result_row(
+                        ui,
+                        word,
+                        text,
+                        &mut self.inflections,
+                        &self.notes,
+                        &self.collected,
+                        score,
+                    ) {
+                        action = Some(a);
+                    }
+                };
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show_rows(ui, row_height, rows.len(), |ui, range| {
+                        for row in &rows[range] {
+                            let layout = egui::Layout::left_to_right(egui::Align::Center);
+                            let row_size = egui::vec2(ui.available_width(), row_height);
+                            ui.allocate_ui_with_layout(row_size, layout, |ui| {
+                                ui.set_min_height(row_height);
+                                if columns == 1 || !row[0].is_word() {
+                                    show_line(ui, &row[0]);
+                                    return;
+                                }
+                                ui.columns(columns, |cells| {
+                                    for (ui, line) in cells.iter_mut().zip(row) {
+                                        ui.with_layout(layout, |ui| show_line(ui, line));
+                                    }
+                                });
+                            });
+                        }
+                    });
+            }
+            Err(e) => {
+                let mut remove_unknown = false;
+                egui::Frame::group(ui.style()).show(ui, |ui| {
+                    ui.colored_label(self.accent, RichText::new(e.to_string()).size(14.0));
+
+                    ui.horizontal_wrapped(|ui| {
+                        if ui.small_button(tr("Копировать")).clicked() {
+                            ui.output_mut(|o| o.copied_text = e.to_string());
+                        }
+
+                        if let Some(word) = &self.unknown_query {
+                            if ui
+                                .small_button(tr("Добавить слово"))
+                                .on_hover_text(tr("Указать ударение и научить словарь этому слову"))
+                                .clicked()
+                            {
+                                self.new_word = Some(NewWord::new(word));
+                            }
+                        }
+
+                        if matches!(e, SearchError::UnknownThemeWords(_))
+                            && self.theme == Theme::Custom
+                        {
+                            remove_unknown = ui
+                                .small_button(tr("Убрать их из темы"))
+                                .on_hover_text(tr("Удалить неизвестные слова и повторить поиск"))
+                                .clicked();
+                        }
+                    });
+                });
+
+                if remove_unknown {
+                    self.remove_unknown_theme_words();
+                }
+            }
+        };
+
+        if surprise {
+            self.surprise(ctx);
+        }
+
+        match action {
+            Some(ResultAction::Search(word)) if !self.query_locked => self.swap_query(word),
+            Some(ResultAction::Insert(word)) => {
+                editor::insert_at_cursor(ctx, editor::main_text_id(), &mut self.main_text, &word)
+            }
+            Some(ResultAction::EditNote(word)) => self.edit_note(word),
+            Some(ResultAction::Define(word)) => {
+                let url = match self.dictionary_url.contains("{}") {
+                    true => self.dictionary_url.replace("{}", &word),
+                    false => format!("{}{word}", self.dictionary_url),
+                };
+                ctx.open_url(egui::OpenUrl::new_tab(url));
+            }
+            Some(ResultAction::ToggleCollected(word)) => {
+                match self.collected.iter().position(|w| *w == word) {
+                    Some(i) => {
+                        self.collected.remove(i);
+                    }
+                    None => self.collected.push(word),
+                }
+            }
+            Some(ResultAction::ExcludeSpeechPart(code)) => {
+                if let Some(flag) = self.rps.flag(&code) {
+                    *flag = true;
+                    self.search();
+                }
+            }
+            // the query is locked
+            Some(ResultAction::Search(_)) | None => {}
+        }
+    }
+
+    fn show_new_word_window(&mut self, ctx: &egui::Context) {
+        let Some(new_word) = &mut self.new_word else {
+            return;
+        };
+
+        match new_word.show(ctx) {
+            Some(true) => {
+                let NewWord { word, stress } = self.new_word.take().expect("checked above");
+                self.custom_words.insert(word.clone(), stress);
+                self.rhyme_word = word;
+                self.search();
+            }
+            Some(false) => self.new_word = None,
+            None => {}
+        }
+    }
+
+    fn show_clear_data_window(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let Some(understood) = &mut self.clearing_data else {
+            return;
+        };
+
+        let mut done = None;
+        egui::Window::new(tr("Очистить все данные"))
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(tr(
+                    "Будут удалены текст, заметки, пользовательские слова, пресеты, \
+                    тема и все остальные сохранённые настройки. Это нельзя отменить.",
+                ));
+                ui.checkbox(understood, tr("Я понимаю"));
+                ui.horizontal(|ui| {
+                    let clear =
+                        egui::Button::new(RichText::new(tr("Очистить")).color(Color32::RED));
+                    if ui.add_enabled(*understood, clear).clicked() {
+                        done = Some(true);
+                    }
+                    if ui.button(tr("Отмена")).clicked() {
+                        done = Some(false);
+                    }
+                });
+            });
+
+        match done {
+            Some(true) => {
+                // the message promises the presets are gone, their files too
+                #[cfg(not(target_arch = "wasm32"))]
+                for name in self.presets.keys() {
+                    presets::remove_file(name);
+                }
+                // the dictionary stays loaded, it isn't the user's data
+                let cleared = Self {
+                    dictionary_loaded: self.dictionary_loaded,
+                    dictionary_loading: self.dictionary_loading.take(),
+                    ..Self::default()
+                };
+                *self = cleared;
+
+                if let Some(storage) = frame.storage_mut() {
+                    eframe::set_value(storage, eframe::APP_KEY, self);
+                    eframe::set_value(storage, DRAFT_KEY, &self.main_text);
+                    storage.flush();
+                }
+            }
+            Some(false) => self.clearing_data = None,
+            None => {}
+        }
+    }
+
+    /// The query, the settings toggles and the results, in the side panel or the drawer.
+    fn show_rhymes_panel(
+        &mut self,
+        ctx: &egui::Context,
+        ui: &mut Ui,
+        loaded: bool,
+        focus_query: bool,
+    ) {
+        if !loaded {
+            ui.add_enabled(
+                false,
+                TextEdit::singleline(&mut String::new()).hint_text(tr("К чему рифму?")),
+            );
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            ui.toggle_value(&mut self.show_theme, tr("Тема"));
+            ui.toggle_value(&mut self.show_settings, tr("Параметры рифм"));
+            ui.toggle_value(&mut self.strict_rhyme, tr("Точная рифма"))
+                .on_hover_text(tr(
+                    "Только рифмы с совпадающими ударениями; параметры ударений не меняются",
+                ));
+            ComboBox::from_id_source("rhyme_mode")
+                .selected_text(tr(self.rhyme_mode.name()))
+                .show_ui(ui, |ui| {
+                    for m in RhymeMode::ALL {
+                        ui.selectable_value(&mut self.rhyme_mode, m, tr(m.name()));
+                    }
+                })
+                .response
+                .on_hover_text(tr(
+                    "Что должно совпадать: всё после ударной гласной или в основном ударный слог",
+                ));
+        });
+
+        if self.show_theme {
+            self.show_theme_select(ui);
+        }
+
+        ui.horizontal(|ui| {
+            let unlocked = !self.query_locked;
+            if ui
+                .add_enabled(
+                    unlocked && !self.query_back.is_empty(),
+                    egui::Button::new("⏴"),
+                )
+                .on_hover_text(tr("Предыдущий запрос"))
+                .clicked()
+            {
+                self.go_back();
+            }
+            if ui
+                .add_enabled(
+                    unlocked && !self.query_forward.is_empty(),
+                    egui::Button::new("⏵"),
+                )
+                .on_hover_text(tr("Следующий запрос"))
+                .clicked()
+            {
+                self.go_forward();
+            }
+            ui.toggle_value(&mut self.query_locked, "🔒")
+                .on_hover_text(tr(
+                    "Закрепить запрос, чтобы его не меняли переходы по рифмам",
+                ));
+            if ui
+                .add_enabled(unlocked, egui::Button::new("🎲"))
+                .on_hover_text(tr("Случайное слово"))
+                .clicked()
+            {
+                self.random_query(ctx);
+            }
+            if self.pending.is_some() {
+                ui.spinner().on_hover_text(tr("Идёт поиск"));
+            }
+            let clear = ui
+                .add_enabled(
+                    unlocked && !self.rhyme_word.is_empty(),
+                    egui::Button::new("✕"),
+                )
+                .on_hover_text(tr("Очистить запрос"))
+                .clicked();
+            if clear {
+                self.clear_query();
+            }
+
+            let input = TextEdit::singleline(&mut self.rhyme_word)
+                .interactive(unlocked)
+                .font(FontId {
+                    size: 20.0,
+                    family: egui::FontFamily::Monospace,
+                })
+                .hint_text(tr("К чему рифму?"));
+
+            let response = ui
+                .add_sized(ui.available_size(), input)
+                .on_hover_text(tr("Ctrl+F — к запросу, Escape — обратно к тексту"));
+            if clear {
+                response.surrender_focus();
+            }
+            if focus_query {
+                response.request_focus();
+            }
+            // the input drops the focus on Escape itself, back to the text then
+            if response.lost_focus() && ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+                ctx.memory_mut(|m| m.request_focus(editor::main_text_id()));
+            }
+
+            if response.changed() {
+                self.query_edited_at = Some(ctx.input(|i| i.time));
+            }
+            if response.lost_focus() && ctx.input(|i| i.key_pressed(egui::Key::Enter)) {
+                self.search();
+            }
+        });
+
+        if self.live_search {
+            self.search_typed_query(ctx);
+        }
+
+        let rhymed = search::rhymed_word(&self.rhyme_word);
+        if rhymed != self.rhyme_word.trim() {
+            ui.label(
+                RichText::new(format!("{} «{rhymed}»", tr("Рифма к последнему слову")))
+                    .italics()
+                    .weak(),
+            );
+        }
+
+        self.show_query_info(ui);
+        self.show_recent_searches(ui);
+        self.show_collected(ctx, ui);
+        self.show_results(ctx, ui);
+
+        /*
+        ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
+            ui.add_space(20.0);
+            ui.toggle_value(&mut self.show_settings, tr("Параметры рифм"));
+        })
+        */
+    }
+
+    /// Saves the main text if it was edited since the last time, so that a
+    /// crash or a closed tab loses only the last seconds.
+    fn autosave(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let (text, saved_at) = &self.autosaved;
+        if *text == self.main_text {
+            return;
+        }
+        let waited = ctx.input(|i| i.time) - saved_at;
+        if waited < AUTOSAVE_INTERVAL {
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(
+                AUTOSAVE_INTERVAL - waited,
+            ));
+            return;
+        }
+
+        if let Some(storage) = frame.storage_mut() {
+            eframe::set_value(storage, DRAFT_KEY, &self.main_text);
+            storage.flush();
+        }
+        self.autosaved = (self.main_text.clone(), ctx.input(|i| i.time));
+    }
+
+    fn show_draft_toast(&mut self, ctx: &egui::Context) {
+        if self.draft_replaced.is_none() {
+            return;
+        }
+        egui::Area::new("draft_restored")
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -40.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(tr("Восстановлен черновик"))
+                            .on_hover_text(tr("Текст не успел сохраниться при закрытии"));
+                        if ui.small_button(tr("Вернуть сохранённый текст")).clicked()
+                        {
+                            // the draft stays in the undo history
+                            self.main_text = self.draft_replaced.take().unwrap_or_default();
+                        }
+                        if ui.small_button("✕").clicked() {
+                            self.draft_replaced = None;
+                        }
+                    });
+                });
+            });
+    }
+
+    fn show_export_error(&mut self, ctx: &egui::Context) {
+        let Some(e) = &self.export_error else {
+            return;
+        };
+        let mut dismissed = false;
+        egui::Area::new("export_error")
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -80.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(
+                            Color32::RED,
+                            format!("{}: {e}", tr("Не удалось сохранить файл")),
+                        );
+                        dismissed = ui.small_button("✕").clicked();
+                    });
+                });
+            });
+        if dismissed {
+            self.export_error = None;
+        }
+    }
+
+    /// Searches the query once it hasn't been edited for a moment.
+    fn search_typed_query(&mut self, ctx: &egui::Context) {
+        let Some(edited_at) = self.query_edited_at else {
+            return;
+        };
+        let waited = ctx.input(|i| i.time) - edited_at;
+        if waited < LIVE_SEARCH_DELAY {
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(
+                LIVE_SEARCH_DELAY - waited,
+            ));
+            return;
+        }
+
+        self.query_edited_at = None;
+        if !self.rhyme_word.trim().is_empty() {
+            self.search();
+        }
+    }
+
+    /// Searches rhymes for the line with the caret once it stays there for a moment.
+    fn follow_cursor_line(&mut self, ctx: &egui::Context) {
+        let Some(cursor) = editor::cursor(ctx, editor::main_text_id()) else {
+            return;
+        };
+        let now = ctx.input(|i| i.time);
+
+        let line = editor::line_of(&self.main_text, cursor);
+        if self.cursor_line != Some(line) {
+            self.cursor_line = Some(line);
+            self.cursor_moved_at = Some(now);
+        }
+
+        let Some(moved_at) = self.cursor_moved_at else {
+            return;
+        };
+        let waited = now - moved_at;
+        if waited < FOLLOW_CURSOR_DELAY {
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(
+                FOLLOW_CURSOR_DELAY - waited,
+            ));
+            return;
+        }
+
+        self.cursor_moved_at = None;
+        if let Some(word) = editor::line_end_word(&self.main_text, cursor) {
+            if word != self.rhyme_word {
+                self.swap_query(word.to_string());
+            }
+        }
+    }
+
+    fn show_templates_menu(&mut self, ui: &mut Ui) {
+        let mut remove = None;
+        for (i, template) in self.templates.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .button(template.shown_name())
+                    .on_hover_text(&template.scheme)
+                    .clicked()
+                {
+                    editor::insert_at_cursor(
+                        ui.ctx(),
+                        editor::main_text_id(),
+                        &mut self.main_text,
+                        &template.skeleton(),
+                    );
+                    self.expected_scheme = Some(template.letters());
+                    ui.close_menu();
+                }
+                if ui.small_button("🗑").on_hover_text(tr("Удалить")).clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            self.templates.remove(i);
+        }
+
+        ui.separator();
+        if ui.button(tr("Новый шаблон…")).clicked() {
+            self.new_template = Some(PoemTemplate {
+                name: String::new(),
+                scheme: String::new(),
+            });
+            ui.close_menu();
+        }
+        if ui.button(tr("Вернуть встроенные")).clicked() {
+            self.templates = PoemTemplate::defaults();
+        }
+    }
+
+    fn show_new_template_window(&mut self, ctx: &egui::Context) {
+        let Some(template) = &mut self.new_template else {
+            return;
+        };
+
+        let mut done = None;
+        egui::Window::new(tr("Новый шаблон"))
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.add(TextEdit::singleline(&mut template.name).hint_text(tr("Название")));
+                ui.add(
+                    TextEdit::singleline(&mut template.scheme)
+                        .hint_text(tr("Схема рифмовки, например АБАБ ВГВГ")),
+                );
+                let valid = !template.name.trim().is_empty()
+                    && template.scheme.chars().any(char::is_alphabetic);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(valid, egui::Button::new(tr("Сохранить")))
+                        .clicked()
+                    {
+                        done = Some(true);
+                    }
+                    if ui.button(tr("Отмена")).clicked() {
+                        done = Some(false);
+                    }
+                });
+            });
+
+        if let Some(save) = done {
+            let template = self.new_template.take().expect("checked above");
+            if save {
+                self.templates.push(template);
+            }
+        }
+    }
+
+    fn edit_note(&mut self, word: String) {
+        let text = self.notes.get(&word).cloned().unwrap_or_default();
+        self.editing_note = Some((word, text));
+    }
+
+    /// The words taught to the dictionary.
+    fn show_custom_words_window(&mut self, ctx: &egui::Context) {
+        let Some(input) = &mut self.custom_words_input else {
+            return;
+        };
+
+        let mut open = true;
+        let mut removed = None;
+        egui::Window::new(tr("Свои слова"))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for (word, &stress) in &self.custom_words {
+                            ui.horizontal(|ui| {
+                                ui.label(custom_words::mark_stress(word, stress));
+                                if ui.small_button("🗑").on_hover_text(tr("Удалить")).clicked()
+                                {
+                                    removed = Some(word.clone());
+                                }
+                            });
+                        }
+                    });
+
+                if self.custom_words != custom_words::merged_words() {
+                    ui.label(
+                        RichText::new(tr("Среди рифм изменения появятся после перезапуска"))
+                            .small()
+                            .weak(),
+                    );
+                }
+                ui.label(
+                    RichText::new(tr(
+                        "В темах свои слова не работают: их смысл словарю неизвестен",
+                    ))
+                    .small()
+                    .weak(),
+                );
+
+                ui.separator();
+                ui.add(TextEdit::multiline(input).hint_text(tr(
+                    "По слову в строке, апостроф после ударной гласной: глока'я",
+                )));
+                let parsed: Vec<_> = input
+                    .lines()
+                    .filter_map(custom_words::parse_marked)
+                    .collect();
+                let wrong = input.lines().filter(|l| !l.trim().is_empty()).count() - parsed.len();
+                if wrong > 0 {
+                    ui.label(
+                        RichText::new(format!("{}: {wrong}", tr("Без отмеченного ударения")))
+                            .small()
+                            .color(Color32::RED),
+                    );
+                }
+                if ui
+                    .add_enabled(!parsed.is_empty(), egui::Button::new(tr("Добавить")))
+                    .clicked()
+                {
+                    self.custom_words.extend(parsed);
+                    input.clear();
+                }
+            });
+
+        if let Some(word) = removed {
+            self.custom_words.remove(&word);
+        }
+        if !open {
+            self.custom_words_input = None;
+        }
+    }
+
+    fn show_note_window(&mut self, ctx: &egui::Context) {
+        let Some((word, text)) = &mut self.editing_note else {
+            return;
+        };
+
+        let mut done = None;
+        egui::Window::new(format!("{}: {word}", tr("Заметка")))
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.add(
+                    TextEdit::multiline(text).hint_text(tr("Коннотации, где уже использовано…")),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Сохранить")).clicked() {
+                        done = Some(true);
+                    }
+                    if ui.button(tr("Отмена")).clicked() {
+                        done = Some(false);
+                    }
+                });
+            });
+
+        if let Some(save) = done {
+            let (word, text) = self.editing_note.take().expect("checked above");
+            if !save {
+                return;
+            }
+            if text.trim().is_empty() {
+                self.notes.remove(&word);
+            } else {
+                self.notes.insert(word, text);
+            }
+        }
+    }
+
+    fn current_query(&self) -> SearchQuery {
+        SearchQuery {
+            word: self.rhyme_word.clone(),
+            core_word: self.query_word(),
+            settings: self.rhyme_mode.apply(&match self.strict_rhyme {
+                true => search::strict(&self.general_settings),
+                false => self.general_settings.clone(),
+            }),
+            // searching without the theme is better than not searching at all
+            theme: match *THEMES_AVAILABLE {
+                true => self.theme.clone(),
+                false => Theme::No,
+            },
+            custom_theme_text: match &self.theme {
+                Theme::User(name) => self.user_themes.get(name).cloned().unwrap_or_default(),
+                Theme::Custom => self.custom_theme_text.clone(),
+                _ => String::new(),
+            },
+            remove_parts_of_speech: self.rps.get_list(),
+            show_rhymes: self.show_rhymes,
+            search_rhymes: self.search_rhymes,
+            show_homophones: self.show_homophones,
+            loose_pass: self.loose_pass,
+            score_parts: self.show_score_parts,
+            syllables: (self.min_syllables, self.max_syllables),
+            excluded_words: self.excluded_words.clone(),
+            exclude_same_root: self.exclude_same_root,
+            merge_yo: self.merge_yo,
+        }
+    }
+
+    /// Whether the query or settings changed since the displayed results were found.
+    fn results_stale(&self) -> bool {
+        self.searched
+            .as_ref()
+            .is_some_and(|searched| *searched != self.current_query())
+    }
+
+    /// The query as the core should see it, with the stress of a custom word.
+    fn query_word(&self) -> String {
+        let word = search::rhymed_word(&self.rhyme_word);
+        match &self.query_stress {
+            Some((stressed, stress)) if stressed == word => {
+                custom_words::mark_stress(word, *stress)
+            }
+            _ => self.core_word(word),
+        }
+    }
+
+    /// The word as the core should see it, with the stress of a custom word.
+    fn core_word(&self, word: &str) -> String {
+        match self.custom_words.get(word) {
+            Some(&stress) => custom_words::mark_stress(word, stress),
+            None => word.to_string(),
+        }
+    }
+
+    fn remove_unknown_theme_words(&mut self) {
+        let Err(SearchError::UnknownThemeWords(unknown)) = &self.rhyme_output else {
+            return;
+        };
+        self.custom_theme_text = self
+            .custom_theme_text
+            .split_whitespace()
+            .filter(|w| !unknown.iter().any(|u| u == w))
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.search();
+    }
+
+    fn compare_metrics(&mut self) {
+        let query = self.current_query();
+        let mut sound = query.settings.clone();
+        sound.meaning.weight = 0.0;
+
+        let mut meaning = query.settings.clone();
+        meaning.stresses.weight = 0.0;
+        meaning.consonant_structure.weight = 0.0;
+        meaning.alliteration.weight = 0.0;
+
+        self.comparison = Some(search::spawn("comparison", move || {
+            [
+                search::words(query.find(&sound)),
+                search::words(query.find(&meaning)),
+                search::words(query.find(&query.settings)),
+            ]
+        }));
+    }
+
+    fn show_comparison_window(&mut self, ctx: &egui::Context) {
+        let Some(comparison) = &self.comparison else {
+            return;
+        };
+
+        let mut open = true;
+        let mut insert = None;
+        egui::Window::new(format!("{}: {}", tr("Сравнение метрик"), self.rhyme_word))
+            .open(&mut open)
+            .default_width(500.0)
+            .show(ctx, |ui| {
+                let Some(comparison) = comparison.ready() else {
+                    ui.spinner();
+                    return;
+                };
+                let titles = [tr("Звучание"), tr("Смысл"), tr("Вместе")];
+                ui.columns(3, |columns| {
+                    for ((ui, title), results) in columns.iter_mut().zip(titles).zip(comparison) {
+                        ui.push_id(title, |ui| {
+                            ui.heading(title);
+                            match results {
+                                Ok(words) => {
+                                    if let Some(word) = word_links(ui, words, self.font_size) {
+                                        insert = Some(word);
+                                    }
+                                }
+                                Err(e) => {
+                                    ui.colored_label(Color32::RED, e.to_string());
+                                }
+                            }
+                        });
+                    }
+                });
+            });
+
+        if let Some(word) = insert {
+            editor::insert_at_cursor(ctx, editor::main_text_id(), &mut self.main_text, &word);
+        }
+        if !open {
+            self.comparison = None;
+        }
+    }
+
+    fn compare_with_baseline(&mut self) {
+        let Some(baseline) = &self.baseline else {
+            return;
+        };
+        let query = self.current_query();
+        let baseline = baseline.clone();
+        // the settings the main search ran with, strictness and rhyme mode included
+        let current = query.settings.clone();
+        self.baseline_comparison = Some(search::spawn("baseline", move || {
+            [
+                search::words(query.find(&baseline)),
+                search::words(query.find(&current)),
+            ]
+        }));
+    }
+
+    fn show_baseline_window(&mut self, ctx: &egui::Context) {
+        let Some(comparison) = &self.baseline_comparison else {
+            return;
+        };
+        let size = self.font_size;
+
+        let mut open = true;
+        let mut insert = None;
+        egui::Window::new(format!(
+            "{}: {}",
+            tr("Сравнение с эталоном"),
+            self.rhyme_word
+        ))
+        .open(&mut open)
+        .default_width(400.0)
+        .show(ctx, |ui| {
+            let Some([baseline, current]) = comparison.ready() else {
+                ui.spinner();
+                return;
+            };
+            let (baseline, current) = match (baseline, current) {
+                (Ok(baseline), Ok(current)) => (baseline, current),
+                (Err(e), _) | (_, Err(e)) => {
+                    ui.colored_label(Color32::RED, e.to_string());
+                    return;
+                }
+            };
+            let places: HashMap<&str, usize> = baseline
+                .iter()
+                .enumerate()
+                .map(|(i, w)| (w.as_str(), i))
+                .collect();
+            ui.columns(2, |columns| {
+                columns[0].heading(tr("Эталон"));
+                if let Some(word) = word_links(&mut columns[0], baseline, size) {
+                    insert = Some(word);
+                }
+
+                let ui = &mut columns[1];
+                ui.heading(tr("Текущие"));
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (i, word) in current.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let change = place_change(places.get(word.as_str()).copied(), i);
+                            if ui
+                                .link(RichText::new(word).size(size))
+                                .on_hover_text(tr("Вставить в текст"))
+                                .clicked()
+                            {
+                                insert = Some(word.clone());
+                            }
+                            if let Some((mark, color)) = change {
+                                ui.label(RichText::new(mark).small().color(color));
+                            }
+                        });
+                    }
+                });
+            });
+        });
+
+        if let Some(word) = insert {
+            editor::insert_at_cursor(ctx, editor::main_text_id(), &mut self.main_text, &word);
+        }
+        if !open {
+            self.baseline_comparison = None;
+        }
+    }
+
+    /// Alliterative words rather than rhymes; the user's settings stay as they are.
+    fn find_sound_alike(&mut self) {
+        let mut settings = self.general_settings.clone();
+        settings.stresses.weight = 0.0;
+        settings.meaning.weight = 0.0;
+        settings.alliteration.weight = 10.0;
+        let query = self.current_query();
+        self.sound_alike = Some(search::spawn("sound alike", move || {
+            search::words(query.find_with(&settings, None))
+        }));
+    }
+
+    fn show_sound_alike_window(&mut self, ctx: &egui::Context) {
+        let Some(found) = &self.sound_alike else {
+            return;
+        };
+
+        let mut open = true;
+        let mut insert = None;
+        egui::Window::new(format!("{}: {}", tr("Созвучия"), self.rhyme_word))
+            .open(&mut open)
+            .show(ctx, |ui| match found.ready() {
+                Some(Ok(words)) => insert = word_links(ui, words, self.font_size),
+                Some(Err(e)) => {
+                    ui.colored_label(Color32::RED, e.to_string());
+                }
+                None => {
+                    ui.spinner();
+                }
+            });
+
+        if let Some(word) = insert {
+            editor::insert_at_cursor(ctx, editor::main_text_id(), &mut self.main_text, &word);
+        }
+        if !open {
+            self.sound_alike = None;
+        }
+    }
+
+    fn find_line_rhymes(&mut self) {
+        let query = self.current_query();
+        let mut endings = vec![];
+        for line in self.main_text.lines() {
+            let Some(word) = editor::last_word(line) else {
+                continue;
+            };
+            let query = SearchQuery {
+                word: word.to_string(),
+                core_word: self.core_word(word),
+                ..query.clone()
+            };
+            self.line_rhymes.find(&query);
+            endings.push((line.trim().to_string(), query.core_word));
+        }
+        self.line_endings = Some(endings);
+    }
+
+    fn show_line_rhymes_window(&mut self, ctx: &egui::Context) {
+        let Some(endings) = &self.line_endings else {
+            return;
+        };
+
+        self.line_rhymes.poll();
+        let mut open = true;
+        let mut insert = None;
+        let mut refresh = false;
+        egui::Window::new(tr("Рифмы для всех строк"))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                refresh = ui
+                    .button(tr("Обновить"))
+                    .on_hover_text(tr("Пересчитать для изменённых строк"))
+                    .clicked();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (i, (line, word)) in endings.iter().enumerate() {
+                        egui::CollapsingHeader::new(line)
+                            .id_source(i)
+                            .default_open(true)
+                            .show(ui, |ui| match self.line_rhymes.get(word) {
+                                Some(Ok(words)) => {
+                                    ui.horizontal_wrapped(|ui| {
+                                        for w in words {
+                                            if ui
+                                                .link(w)
+                                                .on_hover_text(tr("Вставить в текст"))
+                                                .clicked()
+                                            {
+                                                insert = Some(w.clone());
+                                            }
+                                        }
+                                    });
+                                }
+                                Some(Err(e)) => {
+                                    ui.colored_label(Color32::RED, e.to_string());
+                                }
+                                None => {
+                                    ui.spinner();
+                                }
+                            });
+                    }
+                });
+            });
+
+        if let Some(word) = insert {
+            editor::insert_at_cursor(ctx, editor::main_text_id(), &mut self.main_text, &word);
+        }
+        if refresh {
+            self.find_line_rhymes();
+        }
+        if !open {
+            self.line_endings = None;
+        }
+    }
+
+    /// Searches again once the words are analyzed differently, whether by the
+    /// checkbox, a preset or a loaded file: the old results are simply wrong.
+    fn research_if_reanalyzed(&mut self) {
+        let indexation = self.general_settings.stresses.indexation;
+        if self
+            .searched
+            .as_ref()
+            .is_some_and(|searched| searched.settings.stresses.indexation != indexation)
+        {
+            self.search();
+        }
+    }
+
+    fn search(&mut self) {
+        self.query_edited_at = None;
+        self.results_shown = RESULTS_PAGE;
+        let query = self.current_query();
+        self.searched = Some(query.clone());
+        self.pending = Some(PendingSearch::start(query));
+    }
+
+    /// Picks up the preview and the results of the background search once they are ready.
+    fn poll_search(&mut self) {
+        let Some(mut pending) = self.pending.take() else {
+            return;
+        };
+
+        if let Some(preview) = pending.take_preview() {
+            if preview.is_ok() {
+                self.rhyme_output = preview;
+                self.homophones.clear();
+                self.score_parts.clear();
+                self.loose_output.clear();
+                self.restored_results = false;
+                pending.preview_shown = true;
+                self.found_total = None;
+            }
+        }
+
+        let Some(outcome) = pending.take_outcome() else {
+            self.pending = Some(pending);
+            return;
+        };
+        let word = pending.query.word;
+
+        self.rhyme_output = outcome.rhymes;
+        self.homophones = outcome.homophones;
+        self.score_parts = outcome.score_parts;
+        self.loose_output = outcome.loose;
+        self.found_total = Some(outcome.total);
+        self.unknown_query = outcome
+            .unknown_query
+            .then(|| search::rhymed_word(&word).to_string());
+        self.restored_results = false;
+        if self.rhyme_output.is_ok() && !word.trim().is_empty() {
+            self.recent_searches.retain(|w| *w != word);
+            self.recent_searches.insert(0, word.clone());
+            self.recent_searches.truncate(RECENT_COUNT);
+        }
+        if let Ok(res) = &self.rhyme_output {
+            let profile = self
+                .presets
+                .iter()
+                .find(|(_, settings)| **settings == self.general_settings)
+                .map_or(String::new(), |(name, _)| name.clone());
+            self.session_log.push(LoggedSearch {
+                time: session_log::now(),
+                query: word.clone(),
+                profile,
+                top: res.first().map(|r| r.word.src.clone()),
+                count: res.len(),
+            });
+        }
+        self.results_before = match (&self.last_results, &self.rhyme_output) {
+            (Some(last), Ok(res)) if last.query == word => {
+                Some(ResultsBefore::new(last.results.clone(), res))
+            }
+            _ => None,
+        };
+        self.last_results = self.rhyme_output.as_ref().ok().map(|res| LastResults {
+            dictionary: dictionary_version(),
+            query: word,
+            results: res.iter().map(|r| r.word.src.clone()).collect(),
+            scores: res.iter().map(|r| r.score).collect(),
+        });
+    }
+
+    /// Syllables and part of speech of the query word, as the dictionary sees it.
+    fn show_query_info(&mut self, ui: &mut Ui) {
+        let word = self.query_word();
+        if word.trim().is_empty() {
+            return;
+        }
+
+        if !matches!(&self.query_info, Some((w, _)) if *w == word) {
+            let info = string2word(&WORD_COLLECTOR, &word);
+            self.query_info = Some((word, info));
+        }
+
+        match &self.query_info {
+            Some((_, Ok(info))) => {
+                // clicking a syllable moves the stress, for words like замок
+                let mut stress = None;
+                let mut reset = false;
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing.x = 0.0;
+                    let syllables = prosody::syllables(&info.src);
+                    for (i, syllable) in syllables.iter().enumerate() {
+                        let text = match i == info.stress {
+                            true => RichText::new(syllable.to_uppercase()).small().strong(),
+                            false => RichText::new(*syllable).small().weak(),
+                        };
+                        if ui
+                            .selectable_label(i == info.stress, text)
+                            .on_hover_text(tr("Как словарь понял запрос: слоги, ударный выделен; щелчок по слогу переносит ударение"))
+                            .clicked()
+                        {
+                            stress = Some(i);
+                        }
+                        if i + 1 < syllables.len() {
+                            ui.label(RichText::new("·").small().weak());
+                        }
+                    }
+                    ui.label(RichText::new(format!(" ({})", info.speech_part)).small().weak());
+                    if speech::enabled()
+                        && ui.small_button("🔊").on_hover_text(tr("Прочитать вслух")).clicked()
+                    {
+                        let accented = syllables
+                            .iter()
+                            .enumerate()
+                            .map(|(i, s)| match i == info.stress {
+                                true => prosody::accented_syllable(s),
+                                false => s.to_string(),
+                            })
+                            .collect::<String>();
+                        speech::say(&accented);
+                    }
+                    if self.query_stress.is_some() {
+                        reset = ui
+                            .small_button("↺")
+                            .on_hover_text(tr("Вернуть ударение словаря"))
+                            .clicked();
+                    }
+                });
+                if reset {
+                    self.query_stress = None;
+                    self.search();
+                } else if let Some(stress) = stress {
+                    let word = search::rhymed_word(&self.rhyme_word).to_string();
+                    self.query_stress = Some((word, stress));
+                    self.search();
+                }
+            }
+            Some((_, Err(e))) => {
+                ui.label(RichText::new(e).small().color(Color32::RED));
+            }
+            None => {}
+        }
+    }
+
+    fn show_recent_searches(&mut self, ui: &mut Ui) {
+        if self.recent_searches.is_empty() {
+            return;
+        }
+
+        let mut chosen = None;
+        ui.collapsing(tr("Недавние запросы"), |ui| {
+            ui.add_enabled_ui(!self.query_locked, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    for word in &self.recent_searches {
+                        if ui.small_button(word).clicked() {
+                            chosen = Some(word.clone());
+                        }
+                    }
+                });
+            });
+            if ui
+                .add_enabled(
+                    !self.session_log.is_empty(),
+                    egui::Button::new(tr("Экспорт истории (CSV)")),
+                )
+                .on_hover_text(tr(
+                    "Все запросы этого сеанса со временем, пресетом и лучшей рифмой",
+                ))
+                .clicked()
+            {
+                self.export_error =
+                    export::save_text("history.csv", &self.session_log.to_csv()).err();
+            }
+        });
+
+        if let Some(word) = chosen {
+            self.swap_query(word);
+        }
+    }
+
+    fn show_collected(&mut self, ctx: &egui::Context, ui: &mut Ui) {
+        if self.collected.is_empty() {
+            return;
+        }
+
+        let mut insert = None;
+        let mut removed = None;
+        let title = format!("{} ({})", tr("Копилка"), self.collected.len());
+        egui::CollapsingHeader::new(title)
+            .id_source("collected")
+            .show(ui, |ui| {
+                for (i, word) in self.collected.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .link(word)
+                            .on_hover_text(tr("Вставить в текст"))
+                            .clicked()
+                        {
+                            insert = Some(word.clone());
+                        }
+                        if ui
+                            .small_button("📋")
+                            .on_hover_text(tr("Копировать"))
+                            .clicked()
+                        {
+                            ui.output_mut(|o| o.copied_text = word.clone());
+                        }
+                        if ui
+                            .small_button("✕")
+                            .on_hover_text(tr("Убрать из копилки"))
+                            .clicked()
+                        {
+                            removed = Some(i);
+                        }
+                    });
+                }
+                if ui.small_button(tr("Копировать все")).clicked() {
+                    let words: Vec<_> = self.collected.iter().map(String::as_str).collect();
+                    ui.output_mut(|o| {
+                        o.copied_text = export::format_results(&words, self.result_style)
+                    });
+                }
+            });
+
+        if let Some(word) = insert {
+            editor::insert_at_cursor(ctx, editor::main_text_id(), &mut self.main_text, &word);
+        }
+        if let Some(i) = removed {
+            self.collected.remove(i);
+        }
+    }
+
+    /// The chosen meter or the one of the first lines.
+    fn current_meter(&mut self) -> Option<Meter> {
+        if self.meter.is_some() {
+            return self.meter;
+        }
+        let patterns: Vec<_> = self
+            .main_text
+            .lines()
+            .map(|line| self.highlighter.stress_pattern(line))
+            .collect();
+        Meter::detect(patterns.iter().map(String::as_str))
+    }
+
+    fn export_poem_report(&mut self) {
+        let meter = self.current_meter().map(|m| tr(m.name()));
+        let letters = self.highlighter.scheme_letters(&self.main_text);
+        let expected = self.expected_scheme.as_deref();
+        let mismatches = match expected {
+            Some(scheme) => {
+                let scheme: Vec<char> = scheme.chars().collect();
+                self.highlighter.scheme_mismatches(&self.main_text, &scheme)
+            }
+            None => vec![],
+        };
+        let lines: Vec<_> = self
+            .main_text
+            .lines()
+            .zip(letters)
+            .enumerate()
+            .map(|(i, (text, rhyme))| export::AnnotatedLine {
+                text,
+                rhyme,
+                mismatch: mismatches.get(i) == Some(&true),
+                stresses: self.highlighter.stress_pattern(text),
+            })
+            .collect();
+        let report = export::poem_report(&lines, meter, expected);
+        self.export_error = export::save_text("poem.md", &report).err();
+    }
+
+    /// Stress patterns beside the lines of the editor, the lines breaking the meter marked.
+    fn paint_stress_patterns(
+        &mut self,
+        ui: &Ui,
+        gutter: egui::Rect,
+        output: &egui::text_edit::TextEditOutput,
+    ) {
+        let meter = self.current_meter();
+        let font = FontId::monospace(self.font_size * 0.7);
+        let weak = ui.visuals().weak_text_color();
+        let painter = ui.painter_at(gutter);
+
+        let mut lines = self.main_text.split('\n');
+        let mut line_start = true;
+        for row in &output.galley.rows {
+            if line_start {
+                let Some(line) = lines.next() else {
+                    break;
+                };
+                let pattern = self.highlighter.stress_pattern(line);
+                let color = match meter {
+                    Some(meter) if meter.breaks(&pattern) > 0 => self.accent,
+                    _ => weak,
+                };
+                let pos = egui::pos2(gutter.left(), output.text_draw_pos.y + row.rect.min.y);
+                painter.text(pos, egui::Align2::LEFT_TOP, pattern, font.clone(), color);
+            }
+            line_start = row.ends_with_newline;
+        }
+    }
+
+    /// Searches rhymes for a random dictionary word.
+    fn next_random(&mut self, ctx: &egui::Context) -> u64 {
+        if self.rng == 0 {
+            self.rng = ctx.input(|i| i.time).to_bits() | 1;
+        }
+
+        // xorshift64, good enough for picking words
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+
+    fn random_query(&mut self, ctx: &egui::Context) {
+        let count = WORD_COLLECTOR.words.len();
+        if count == 0 {
+            return;
+        }
+        let word = WORD_COLLECTOR.words[(self.next_random(ctx) % count as u64) as usize]
+            .src
+            .clone();
+        self.swap_query(word);
+    }
+
+    /// Inserts one of the found rhymes, the further down the list and the rarer
+    /// the likelier, reaching as far as the creativity allows.
+    /// The rhymes as the list shows them: of the chosen type and length, in the chosen order.
+    fn shown_rhymes<'a>(&self, res: &'a [Rhyme]) -> Vec<&'a Rhyme> {
+        let limits = (self.min_syllables, self.max_syllables);
+        let mut shown: Vec<_> = res
+            .iter()
+            .filter(|rhyme| {
+                self.rhyme_type_filter.map_or(true, |filter| {
+                    RhymeType::of(&WORD_COLLECTOR, &rhyme.word.src) == Some(filter)
+                })
+            })
+            .filter(|rhyme| search::syllables_fit(limits, prosody::syllable_count(&rhyme.word.src)))
+            .collect();
+        if self.result_order == ResultOrder::Popularity {
+            // stable, so equally popular words stay in the order of the score
+            shown.sort_by(|a, b| b.word.popularity.total_cmp(&a.word.popularity));
+        }
+        shown
+    }
+
+    fn surprise(&mut self, ctx: &egui::Context) {
+        let random = self.next_random(ctx);
+        let Ok(res) = &self.rhyme_output else {
+            return;
+        };
+        let shown = self.shown_rhymes(res);
+        if shown.is_empty() {
+            return;
+        }
+        let reach = ((shown.len() as f32 * self.creativity).ceil() as usize).clamp(1, shown.len());
+        let candidates = &shown[..reach];
+        let max_popularity = candidates
+            .iter()
+            .map(|r| r.word.popularity)
+            .fold(0.0, f32::max);
+
+        let weights: Vec<f64> = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let rarity = match max_popularity > 0.0 {
+                    true => 1.0 - r.word.popularity / max_popularity,
+                    false => 0.0,
+                };
+                (i + 1) as f64 * (0.1 + rarity as f64)
+            })
+            .collect();
+        let mut left = random as f64 / u64::MAX as f64 * weights.iter().sum::<f64>();
+        let chosen = weights
+            .iter()
+            .position(|w| {
+                left -= w;
+                left < 0.0
+            })
+            .unwrap_or(reach - 1);
+
+        let word = candidates[chosen].word.src.clone();
+        editor::insert_at_cursor(ctx, editor::main_text_id(), &mut self.main_text, &word);
+    }
+
+    /// Applies the settings file once it's opened.
+    fn poll_settings_file(&mut self) {
+        let Some(contents) = self.settings_file.as_ref().and_then(Promise::ready) else {
+            return;
+        };
+        // missing fields get the default values, anything malformed rejects the whole file
+        if let Some(contents) = contents {
+            match serde_json::from_str::<SettingsFile>(contents) {
+                Ok(file) => {
+                    self.general_settings = file.settings;
+                    self.rps = file.remove_parts_of_speech;
+                    self.settings_file_error = None;
+                }
+                Err(e) => self.settings_file_error = Some(e.to_string()),
+            }
+        }
+        self.settings_file = None;
+    }
+
+    /// Empties the query and the results.
+    fn clear_query(&mut self) {
+        self.rhyme_word.clear();
+        self.rhyme_output = Ok(vec![]);
+        self.homophones.clear();
+        self.loose_output.clear();
+        self.unknown_query = None;
+        self.searched = None;
+        self.pending = None;
+        self.query_edited_at = None;
+        self.restored_results = false;
+    }
+
+    /// Makes `word` the new query, remembering the current one to go back to.
+    fn swap_query(&mut self, word: String) {
+        let previous = std::mem::replace(&mut self.rhyme_word, word);
+        if !previous.is_empty() {
+            self.query_back.push(previous);
+        }
+        self.query_forward.clear();
+        self.search();
+    }
+
+    fn go_back(&mut self) {
+        if let Some(word) = self.query_back.pop() {
+            let current = std::mem::replace(&mut self.rhyme_word, word);
+            self.query_forward.push(current);
+            self.search();
+        }
+    }
+
+    fn go_forward(&mut self) {
+        if let Some(word) = self.query_forward.pop() {
+            let current = std::mem::replace(&mut self.rhyme_word, word);
+            self.query_back.push(current);
+            self.search();
+        }
+    }
+
+    fn show_settings_window(&mut self, ctx: &egui::Context) {
+        egui::Window::new(tr("Параметры подбора рифмы")).open(&mut self.show_settings).constrain(true).show(ctx, |ui| {
+
+            macro_rules! default_or {
+                ($default: expr) => {
+                    $default
+                };
+                ($default: expr, $some: expr) => {
+                    $some
+                };
+            }
 
-        if self.theme == Theme::Custom {
             ui.add(
-                TextEdit::multiline(&mut self.custom_theme_text)
-                    .hint_text("Введите слова, ассоциирующиеся с этой темой"),
+                Slider::new(&mut self.show_rhymes, 1..=500)
+                    .text(tr("Количество отображаемых рифм"))
+            );
+            ui.add(
+                Slider::new(&mut self.search_rhymes, 1..=2000)
+                    .logarithmic(true)
+                    .text(tr("Сколько искать"))
+            )
+            .on_hover_text(tr("Из скольких лучших рифм отбирать показанные; больше — меньше пустеет список при строгих фильтрах, но медленнее"));
+
+            // sets the advanced sliders below, which stay free to be tuned further
+            if ui
+                .add(
+                    Slider::new(&mut self.tolerance, 0.0..=100.0)
+                        .suffix("%")
+                        .text(tr("Допуск неточных рифм")),
+                )
+                .on_hover_text(tr("Один ползунок вместо многих: от точных рифм до самых вольных"))
+                .changed()
+            {
+                self.general_settings =
+                    search::tolerant(&self.general_settings, self.tolerance / 100.0);
+            }
+
+            if let Some(searched) = &self.searched {
+                ui.checkbox(&mut self.preview_settings, tr("Предпросмотр"))
+                    .on_hover_text(tr("Лучшие рифмы последнего запроса с новыми параметрами, пересчитываются на лету; медленно"));
+                if self.preview_settings {
+                    self.settings_preview.update(searched, &self.general_settings);
+                    ui.horizontal_wrapped(|ui| {
+                        match &self.settings_preview.words {
+                            Some(Ok(words)) => {
+                                for word in words {
+                                    ui.label(RichText::new(word).strong());
+                                }
+                            }
+                            Some(Err(e)) => {
+                                ui.colored_label(Color32::RED, e.to_string());
+                            }
+                            None => {}
+                        }
+                        if self.settings_preview.is_running() {
+                            ui.spinner();
+                        }
+                    });
+                }
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button(tr("Сбросить")).clicked() {
+                    self.general_settings = GeneralSettings::default();
+                    self.tolerance = DEFAULT_TOLERANCE;
+                }
+                if ui.button(tr("Запомнить как эталон")).on_hover_text(tr("Чтобы сравнивать с ним результаты изменённых параметров")).clicked() {
+                    self.baseline = Some(self.general_settings.clone());
+                }
+                if let Some(baseline) = &self.baseline {
+                    if ui.add_enabled(*baseline != self.general_settings, egui::Button::new(tr("Вернуть эталон"))).clicked() {
+                        self.general_settings = baseline.clone();
+                    }
+                }
+                if ui.button(tr("Сохранить настройки")).clicked() {
+                    let file = SettingsFile {
+                        settings: self.general_settings.clone(),
+                        remove_parts_of_speech: self.rps.clone(),
+                    };
+                    match serde_json::to_string_pretty(&file) {
+                        Ok(json) => {
+                            self.export_error = export::save_text("quickpoeter.json", &json).err();
+                        }
+                        Err(e) => log::warn!("can't serialize the settings: {e}"),
+                    }
+                }
+                if ui.button(tr("Загрузить настройки")).clicked() {
+                    self.settings_file = Some(export::open_text("json"));
+                }
+            });
+            if let Some(warning) = settings_warning(&self.general_settings) {
+                ui.label(RichText::new(format!("⚠ {}", tr(warning))).small().color(Color32::from_rgb(210, 150, 40)));
+            }
+            if let Some(e) = &self.settings_file_error {
+                ui.colored_label(Color32::RED, format!("{}: {e}", tr("Не удалось прочитать настройки")));
+            }
+
+            ui.collapsing(tr("Пресеты"), |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        TextEdit::singleline(&mut self.preset_name)
+                            .hint_text(tr("Название пресета"))
+                            .desired_width(150.0),
+                    );
+                    let name = self.preset_name.trim();
+                    if ui.add_enabled(!name.is_empty(), egui::Button::new(tr("Сохранить"))).clicked() {
+                        #[cfg(not(target_arch = "wasm32"))]
+                        if let Err(e) = presets::save_file(name, &self.general_settings) {
+                            log::warn!("Can't save preset {name}: {e}");
+                        }
+                        self.presets.insert(name.to_string(), self.general_settings.clone());
+                    }
+                });
+
+                let mut remove = None;
+                for (name, settings) in &self.presets {
+                    ui.horizontal(|ui| {
+                        if ui.button(name).on_hover_text(tr("Загрузить")).clicked() {
+                            self.general_settings = settings.clone();
+                        }
+                        if ui.small_button("🗑").on_hover_text(tr("Удалить")).clicked() {
+                            remove = Some(name.clone());
+                        }
+                    });
+                }
+                if let Some(name) = remove {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    presets::remove_file(&name);
+                    self.presets.remove(&name);
+                }
+            });
+
+            ui.checkbox(&mut self.general_settings.stresses.indexation, tr("Индексация гласных"));
+            ui.checkbox(&mut self.show_homophones, tr("Показывать созвучные слова (омофоны)"));
+            ui.checkbox(&mut self.show_examples, tr("Показывать примеры строк (шаблонные)"));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.show_stress, tr("Показывать ударения"));
+                ui.add_enabled_ui(self.show_stress, |ui| {
+                    ComboBox::from_id_source("stress_mark")
+                        .selected_text(tr(self.stress_mark.name()))
+                        .show_ui(ui, |ui| {
+                            for mark in StressMark::ALL {
+                                ui.selectable_value(&mut self.stress_mark, mark, tr(mark.name()));
+                            }
+                        });
+                });
+            });
+            if speech::AVAILABLE {
+                ui.checkbox(&mut self.read_aloud, tr("Читать слова вслух"));
+            }
+            ui.horizontal(|ui| {
+                ui.label(tr("Словарь значений"));
+                ui.text_edit_singleline(&mut self.dictionary_url)
+                    .on_hover_text(tr("Адрес страницы слова, {} заменяется самим словом"));
+            });
+            ui.checkbox(&mut self.show_changes, tr("Показывать изменения"))
+                .on_hover_text(tr("Отмечать, как сдвинулись рифмы при повторном поиске того же слова"));
+            ui.checkbox(&mut self.show_score_parts, tr("Разбирать оценки рифм"))
+                .on_hover_text(tr("Из чего складывается оценка, при наведении на рифму; поиск медленнее"));
+            ui.add(Slider::new(&mut self.font_size, 12.0..=36.0).text(tr("Размер шрифта")));
+            ui.checkbox(&mut self.loose_pass, tr("Добирать менее точные рифмы"));
+            ui.checkbox(&mut self.merge_yo, tr("Не различать е и ё"))
+                .on_hover_text(tr("Показывать написания с е и с ё одной рифмой"));
+            ui.checkbox(&mut self.exclude_same_root, tr("Без однокоренных слов"))
+                .on_hover_text(tr("Не предлагать формы запроса и слова с тем же корнем, как любить — полюбить"));
+            ui.checkbox(&mut self.live_search, tr("Искать на лету"));
+            ui.horizontal(|ui| {
+                ui.label(tr("Оценка рифм: отличные до"))
+                    .on_hover_text(tr("Чем меньше оценка, тем лучше рифма"));
+                ui.add(egui::DragValue::new(&mut self.quality_thresholds[0]).speed(0.5));
+                ui.label(tr("хорошие до"));
+                ui.add(egui::DragValue::new(&mut self.quality_thresholds[1]).speed(0.5));
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("Слогов в рифме"));
+                ui.add(egui::DragValue::new(&mut self.min_syllables).prefix(tr("от ")));
+                ui.add(egui::DragValue::new(&mut self.max_syllables).prefix(tr("до ")))
+                    .on_hover_text(tr("0 — без ограничения"));
+            });
+
+            egui::ScrollArea::vertical()
+                .auto_shrink([false; 2])
+                .show(ui, |ui| {
+                    // every section resets only its own fields
+                    macro_rules! setting {
+                        ($settings: expr, $block: expr => {$($name: literal, $($field: ident).+, $max: expr $(, $min: expr)?;)*}) => {
+                            egui::collapsing_header::CollapsingState::load_with_default_open(
+                                ui.ctx(),
+                                ui.make_persistent_id($block),
+                                false,
+                            )
+                            .show_header(ui, |ui| {
+                                ui.label($block);
+                                if ui
+                                    .small_button("↺")
+                                    .on_hover_text(tr("Вернуть значения по умолчанию"))
+                                    .clicked()
+                                {
+                                    let default = GeneralSettings::default();
+                                    $( $settings.$($field).+ = default.$($field).+; )*
+                                }
+                            })
+                            .body(|ui| {
+                                $(
+                                    let response = ui.add(
+                                        Slider::new(&mut $settings.$($field).+, default_or!(0.0$(, $min)? )..=$max)
+                                            .clamp_to_range(false)
+                                            .text(tr($name)),
+                                    );
+                                    if let Some(help) = help::slider_help($name) {
+                                        response.on_hover_text(tr(help));
+                                    }
+                                )*
+                            })
+                        };
+                    }
+
+                    setting!(self.general_settings, tr("Веса") => {
+                        "Тематика", meaning.weight, 5000.0;
+                        "Популярность слова", popularity.weight, 1e-5;
+                        "Ударения", stresses.weight, 200.0;
+                        "Структура", consonant_structure.weight, 10.0;
+                        "Аллитерации", alliteration.weight, 10.0;
+                    });
+
+                    setting!(self.general_settings, tr("Тематика") => {
+                        "Степень", meaning.pow, 5.0;
+                        "Степень для одного слова", meaning.single_pow, 5.0;
+                        "Множитель для одного слова", meaning.single_weight, 1.0;
+                        "Вес", meaning.weight, 5000.0;
+                    });
+
+                    setting!(self.general_settings, tr("Разное") => {
+                        "Близкая длина", misc.length_diff_fine, 3.0;
+                        "Совпадающие гласные/согласные в конце", misc.same_cons_end, 3.0;
+                    });
+
+                    setting!(self.general_settings, tr("Популярность слова") => {
+                        "Вес", popularity.weight, 1e-5;
+                        "Степень", popularity.pow, 5.0;
+                    });
+
+                    setting!(self.general_settings, tr("Ударения") => {
+                        "Строгие ударения", stresses.k_strict_stress, 40.0;
+                        "Нестрогие ударения", stresses.k_not_strict_stress, 7.0;
+                        "Штраф за плохой ритм", stresses.bad_rythm, 100.0;
+                        "Сдвиг веса сравнения гласных", stresses.shift_syll_ending, 3.0;
+                        "Степень веса сравнения гласных", stresses.pow_syll_ending, 3.0;
+                        "Ассимптотика метрики", stresses.asympt, 3.0;
+                        "Сдвиг ассимптотики метрики", stresses.asympt_shift, 2.0;
+                        "Вес", stresses.weight, 200.0;
+                    });
+
+                    setting!(self.general_settings, tr("Длина искомой рифмы") => {
+                        "Идеальная длина", unsymmetrical.optimal_length, 15.0;
+                        "Вес штрафа меньших", unsymmetrical.less_w, 0.5;
+                        "Степень штрафа меньших", unsymmetrical.less_pow, 1.1;
+                        "Вес штрафа больших", unsymmetrical.more_w, 0.5;
+                        "Степень штрафа больших", unsymmetrical.more_pow, 1.1;
+                    });
+
+                    speech_part_presets(ui, &mut self.general_settings);
+
+                    setting!(self.general_settings, tr("Штрафы за совпадающие части речи") => {
+                        "Глаголы", same_speech_part.verb, 2.0;
+                        "Прилагательные", same_speech_part.adj, 1.0;
+                        "Существительные", same_speech_part.noun, 1.0;
+                        "Наречия", same_speech_part.adv, 1.0;
+                    });
+
+                    setting!(self.general_settings, tr("Структура") => {
+                        "Степень разности длин слогов", consonant_structure.pow, 5.0;
+                        "Сдвиг множителя сравнения с конца", consonant_structure.shift_syll_ending, 5.0;
+                        "Степень множителя сравнения с конца", consonant_structure.pow_syll_ending, 5.0;
+                        "Ассимптотика метрики", consonant_structure.asympt, 3.0;
+                        "Сдвиг ассимптотики", consonant_structure.asympt_shift, 5.0;
+                        "Вес", consonant_structure.weight, 10.0;
+                    });
+
+                    setting!(self.general_settings, tr("Аллитерации") => {
+                        "Сдвиг расстояния в слове между буквами", alliteration.shift_coord, 5.0;
+                        "Степень расстояния в слове между буквами", alliteration.pow_coord_delta, 5.0;
+                        "Сдвиг важности согласных в концовке", alliteration.shift_syll_ending, 5.0;
+                        "Степень важности согласных в концовке", alliteration.pow_syll_ending, 3.0, -3.0;
+                        "Штраф за дополнительные звуки", alliteration.permutations, 50.0;
+                        "Ассимптотика метрики", alliteration.asympt, 3.0;
+                        "Сдвиг ассимптотики", alliteration.asympt_shift, 5.0;
+                        "Вес", alliteration.weight, 10.0;
+                    });
+
+                    ui.collapsing(tr("Исключить части речи"), |ui| {
+                        rps_presets_editor(ui, &mut self.rps, &mut self.rps_presets, &mut self.rps_preset_name);
+                        ui.checkbox(&mut self.rps.noun, tr("Существительные"));
+                        ui.checkbox(&mut self.rps.adj, tr("Прилагательные"));
+                        ui.checkbox(&mut self.rps.pronoun, tr("Местоимения"));
+                        ui.checkbox(&mut self.rps.pronoun_adj, tr("Местоимения-прилагательные"));
+                        ui.checkbox(&mut self.rps.verb, tr("Глаголы"));
+                        ui.checkbox(&mut self.rps.adv, tr("Наречия"));
+                        ui.checkbox(&mut self.rps.num, tr("Числительные"));
+                        ui.checkbox(&mut self.rps.num_adj, tr("Счётные прилагательные"));
+                        ui.checkbox(&mut self.rps.linking, tr("Вводные"));
+                        ui.checkbox(&mut self.rps.citoslovce, tr("Междометия"));
+                        ui.checkbox(&mut self.rps.pred, tr("Предикативы"));
+                        ui.checkbox(&mut self.rps.prep, tr("Предлоги"));
+                        ui.checkbox(&mut self.rps.conj, tr("Союзы"));
+                        ui.checkbox(&mut self.rps.compare, tr("Сравнительные степени"));
+                        ui.checkbox(&mut self.rps.part, tr("Частицы"));
+                        ui.checkbox(&mut self.rps.misc, tr("Прочее (фразеологизмы, устаревшие…)"));
+                    });
+
+                    ui.collapsing(tr("Исключённые слова"), |ui| {
+                        excluded_words_editor(ui, &mut self.excluded_words, &mut self.new_excluded_word)
+                    });
+                }
+            )
+        });
+    }
+
+    fn step_preset(&mut self, step: isize) {
+        if !*THEMES_AVAILABLE {
+            return;
+        }
+        // in the same order as in the list
+        let mut presets: Vec<_> = MEAN_STR_THEMES.str_themes.keys().collect();
+        presets.sort();
+        if presets.is_empty() {
+            return;
+        }
+
+        let current = match &self.theme {
+            Theme::Preset(s) => presets.iter().position(|p| *p == s),
+            _ => None,
+        };
+        let next = match current {
+            Some(i) => (i as isize + step).rem_euclid(presets.len() as isize) as usize,
+            None if step > 0 => 0,
+            None => presets.len() - 1,
+        };
+        self.theme = Theme::Preset(presets[next].clone());
+    }
+
+    fn show_save_user_theme(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.user_theme_name)
+                    .desired_width(120.0)
+                    .hint_text(tr("Название")),
             );
+            let name = self.user_theme_name.trim();
+            if ui
+                .add_enabled(!name.is_empty(), egui::Button::new(tr("Сохранить тему")))
+                .clicked()
+            {
+                match Theme::Custom.mean_theme(&self.custom_theme_text) {
+                    Ok(_) => {
+                        let name = name.to_string();
+                        self.user_themes
+                            .insert(name.clone(), self.custom_theme_text.clone());
+                        self.theme = Theme::User(name);
+                        self.user_theme_name.clear();
+                        self.user_theme_error = None;
+                    }
+                    Err(unknown) if unknown.is_empty() => {
+                        self.user_theme_error = Some(SearchError::EmptyTheme);
+                    }
+                    Err(unknown) => {
+                        self.user_theme_error = Some(SearchError::UnknownThemeWords(unknown));
+                    }
+                }
+            }
+        });
+        if let Some(e) = &self.user_theme_error {
+            ui.colored_label(Color32::RED, e.to_string());
+        }
+    }
+
+    fn show_theme_select(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        if !*THEMES_AVAILABLE {
+            ui.add_enabled(false, egui::Label::new(tr("Тематический поиск недоступен")))
+                .on_disabled_hover_text(tr(
+                    "Не удалось загрузить векторы слов, рифмы ищутся без учёта темы",
+                ));
+            return;
+        }
+        let theme_before = self.theme.clone();
+        let list = ComboBox::from_label(tr("Встроенная тема"))
+            .selected_text(self.theme.name())
+            .show_ui(ui, |ui| {
+                let filter = ui
+                    .text_edit_singleline(&mut self.theme_filter)
+                    .on_hover_text(tr("Начните вводить название темы, Enter — выбрать первую"));
+                // only as the list opens, not to take the focus back from the rest of it
+                if !self.theme_list_open {
+                    filter.request_focus();
+                }
+                ui.selectable_value(&mut self.theme, Theme::No, tr("Без темы"));
+
+                ui.selectable_value(&mut self.theme, Theme::Custom, tr("Пользовательская"));
+                ui.separator();
+
+                let filter = self.theme_filter.to_lowercase();
+                let mut matching: Vec<_> = MEAN_STR_THEMES
+                    .str_themes
+                    .keys()
+                    .filter(|s| s.to_lowercase().contains(&filter))
+                    .collect();
+                matching.sort();
+
+                if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    if let Some(s) = matching.first() {
+                        self.theme = Theme::Preset(s.to_string());
+                        ui.memory_mut(|m| m.close_popup());
+                    }
+                }
+
+                if matching.is_empty() {
+                    ui.label(RichText::new(tr("Ничего не найдено")).italics().weak());
+                }
+                for s in matching {
+                    ui.selectable_value(&mut self.theme, Theme::Preset(s.to_string()), s);
+                }
+
+                let mut user = self
+                    .user_themes
+                    .keys()
+                    .filter(|s| s.to_lowercase().contains(&filter))
+                    .peekable();
+                if user.peek().is_some() {
+                    ui.separator();
+                    ui.label(RichText::new(tr("Сохранённые")).italics().weak());
+                }
+                for s in user {
+                    ui.selectable_value(&mut self.theme, Theme::User(s.to_string()), s);
+                }
+            });
+        if self.theme_list_open && list.inner.is_none() {
+            self.theme_filter.clear();
+        }
+        self.theme_list_open = list.inner.is_some();
+
+        if let Theme::User(name) = &self.theme {
+            if ui.small_button(tr("Удалить сохранённую тему")).clicked() {
+                self.user_themes.remove(name);
+                self.theme = Theme::No;
+            }
+        }
+
+        // Alt+↑/↓ walks through the presets without opening the list
+        let step = ui.input(|i| {
+            if !i.modifiers.alt {
+                0
+            } else if i.key_pressed(egui::Key::ArrowDown) {
+                1
+            } else if i.key_pressed(egui::Key::ArrowUp) {
+                -1
+            } else {
+                0
+            }
+        });
+        if step != 0 {
+            self.step_preset(step);
+        }
+        if self.theme != theme_before {
+            self.theme_filter.clear();
+        }
+
+        if self.theme == Theme::Custom {
+            let mut layouter = |ui: &Ui, text: &str, wrap_width: f32| {
+                let font_id = egui::TextStyle::Body.resolve(ui.style());
+                let color = ui.visuals().widgets.inactive.text_color();
+                let mut job = highlighter::theme_words(text, font_id, color);
+                job.wrap.max_width = wrap_width;
+                ui.fonts(|f| f.layout_job(job))
+            };
+            ui.add(
+                TextEdit::multiline(&mut self.custom_theme_text)
+                    .hint_text(tr("Введите слова, ассоциирующиеся с этой темой"))
+                    .layouter(&mut layouter),
+            )
+            .on_hover_text(tr("Слова, которых нет в словаре, выделены красным"));
+            self.show_save_user_theme(ui);
+        }
+        if self.theme != Theme::No {
+            self.show_theme_neighbours(ui);
         }
         ui.add_space(10.0);
     }
+
+    /// What the theme is associated with, to check it before searching.
+    fn show_theme_neighbours(&mut self, ui: &mut Ui) {
+        // as the search has it, to share the built theme with it
+        let query = self.current_query();
+        let key = query.theme.words(&query.custom_theme_text).join(" ");
+
+        if ui.small_button(tr("Показать похожие слова темы")).clicked() {
+            let neighbours = search::spawn("theme neighbours", move || {
+                search::theme_neighbours(&query.settings, &query.theme, &query.custom_theme_text)
+            });
+            self.theme_neighbours = Some((key.clone(), neighbours));
+        }
+
+        // the words changed since, so did the neighbours
+        let Some((_, neighbours)) = self.theme_neighbours.as_ref().filter(|(k, _)| *k == key)
+        else {
+            return;
+        };
+        match neighbours.ready() {
+            None => {
+                ui.spinner();
+            }
+            Some(Ok(neighbours)) => {
+                ui.horizontal_wrapped(|ui| {
+                    for word in neighbours {
+                        ui.label(RichText::new(word).small().weak());
+                    }
+                });
+            }
+            Some(Err(e)) => {
+                ui.colored_label(Color32::RED, e.to_string());
+            }
+        }
+    }
 }
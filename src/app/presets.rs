@@ -0,0 +1,71 @@
+//! Named settings presets. On native they are also kept as separate JSON files,
+//! so they can be shared or put under version control.
+
+use std::collections::BTreeMap;
+
+use quickpoeter::reader::GeneralSettings;
+
+pub type Presets = BTreeMap<String, GeneralSettings>;
+
+#[cfg(not(target_arch = "wasm32"))]
+const PRESETS_DIR: &str = "presets";
+
+/// Replaces characters that can't appear in a file name.
+#[cfg(not(target_arch = "wasm32"))]
+fn file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c => c,
+        })
+        .collect();
+    format!("{name}.json")
+}
+
+/// Reads every preset file in the presets directory, skipping malformed ones.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_dir() -> Presets {
+    let mut presets = Presets::new();
+
+    let Ok(entries) = std::fs::read_dir(PRESETS_DIR) else {
+        return presets;
+    };
+
+    for path in entries.filter_map(Result::ok).map(|e| e.path()) {
+        if path.extension() != Some("json".as_ref()) {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+
+        let settings = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()));
+        match settings {
+            Ok(settings) => {
+                presets.insert(name.to_string(), settings);
+            }
+            Err(e) => log::warn!("Skipping preset {}: {e}", path.display()),
+        }
+    }
+
+    presets
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_file(name: &str, settings: &GeneralSettings) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(PRESETS_DIR).map_err(|e| e.to_string())?;
+    let path = std::path::Path::new(PRESETS_DIR).join(file_name(name));
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remove_file(name: &str) {
+    let path = std::path::Path::new(PRESETS_DIR).join(file_name(name));
+    if let Err(e) = std::fs::remove_file(&path) {
+        log::warn!("Can't remove preset {}: {e}", path.display());
+    }
+}
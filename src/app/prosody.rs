@@ -0,0 +1,248 @@
+use quickpoeter::finder::WordCollector;
+
+const VOWELS: &str = "аеёиоуыэюяАЕЁИОУЫЭЮЯ";
+
+pub fn is_vowel(c: char) -> bool {
+    VOWELS.contains(c)
+}
+
+pub fn syllable_count(word: &str) -> usize {
+    word.chars().filter(|&c| is_vowel(c)).count()
+}
+
+/// Splits `word` into syllables, each ending with its vowel; the last one
+/// also takes the trailing consonants.
+pub fn syllables(word: &str) -> Vec<&str> {
+    let mut syllables = vec![];
+    let mut start = 0;
+    for (i, c) in word.char_indices() {
+        if is_vowel(c) {
+            let end = i + c.len_utf8();
+            syllables.push(&word[start..end]);
+            start = end;
+        }
+    }
+    match syllables.last_mut() {
+        Some(last) => *last = &word[start - last.len()..],
+        None if !word.is_empty() => syllables.push(word),
+        None => {}
+    }
+    syllables
+}
+
+/// How the stress is shown in a word.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Default)]
+pub enum StressMark {
+    /// The accent if the font has it, the capital otherwise.
+    #[default]
+    Auto,
+    /// Combining acute accent, U+0301.
+    Acute,
+    /// The stressed vowel in uppercase.
+    Capital,
+    /// An apostrophe before the stressed vowel.
+    Apostrophe,
+}
+
+impl StressMark {
+    pub const ALL: [Self; 4] = [Self::Auto, Self::Acute, Self::Capital, Self::Apostrophe];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Auto => "Автоматически",
+            Self::Acute => "Знак ударения",
+            Self::Capital => "Заглавная гласная",
+            Self::Apostrophe => "Апостроф",
+        }
+    }
+
+    /// The mark to draw with a font, which may lack the accent.
+    pub fn resolve(self, font_has_accent: bool) -> Self {
+        match self {
+            Self::Auto if font_has_accent => Self::Acute,
+            Self::Auto => Self::Capital,
+            mark => mark,
+        }
+    }
+}
+
+/// `word` with the stressed vowel marked, if the dictionary knows the stress.
+pub fn accented(collector: &WordCollector, word: &str, mark: StressMark) -> String {
+    let stress = match collector.get_word(word) {
+        // nothing to choose from in a single syllable
+        Some(w) if syllable_count(word) > 1 => w.stress,
+        _ => return word.to_string(),
+    };
+
+    let mut syllable = 0;
+    let mut accented = String::with_capacity(word.len() + 2);
+    for c in word.chars() {
+        // ё is always stressed and never marked
+        if !is_vowel(c) || syllable != stress || c == 'ё' {
+            accented.push(c);
+        } else {
+            match mark {
+                StressMark::Auto | StressMark::Acute => {
+                    accented.push(c);
+                    accented.push('\u{301}');
+                }
+                StressMark::Capital => accented.extend(c.to_uppercase()),
+                StressMark::Apostrophe => {
+                    accented.push('\'');
+                    accented.push(c);
+                }
+            }
+        }
+        if is_vowel(c) {
+            syllable += 1;
+        }
+    }
+    accented
+}
+
+/// `syllable` with an acute accent over its vowel, ё being stressed anyway.
+pub fn accented_syllable(syllable: &str) -> String {
+    let mut accented = String::with_capacity(syllable.len() + 2);
+    for c in syllable.chars() {
+        accented.push(c);
+        if is_vowel(c) && c != 'ё' {
+            accented.push('\u{301}');
+        }
+    }
+    accented
+}
+
+/// Part of `word` from the stressed vowel on, the one that has to sound alike in a rhyme.
+pub fn stressed_ending<'a>(collector: &WordCollector, word: &'a str) -> Option<&'a str> {
+    let stress = collector.get_word(&word.to_lowercase())?.stress;
+    let (start, _) = word
+        .char_indices()
+        .filter(|&(_, c)| is_vowel(c))
+        .nth(stress)?;
+    Some(&word[start..])
+}
+
+/// Where the stress falls relative to the end of the word.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RhymeType {
+    /// Stress on the last syllable.
+    Masculine,
+    /// Stress on the penultimate syllable.
+    Feminine,
+    /// Stress on the antepenultimate syllable.
+    Dactylic,
+    /// Stress even further from the end.
+    Hyperdactylic,
+}
+
+impl RhymeType {
+    pub const ALL: [Self; 4] = [
+        Self::Masculine,
+        Self::Feminine,
+        Self::Dactylic,
+        Self::Hyperdactylic,
+    ];
+
+    /// Type of the rhyme ending with `word`, if the dictionary knows its stress.
+    pub fn of(collector: &WordCollector, word: &str) -> Option<Self> {
+        let stress = collector.get_word(word)?.stress;
+        let after_stress = syllable_count(word).checked_sub(stress + 1)?;
+        Some(match after_stress {
+            0 => Self::Masculine,
+            1 => Self::Feminine,
+            2 => Self::Dactylic,
+            _ => Self::Hyperdactylic,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Masculine => "Мужская",
+            Self::Feminine => "Женская",
+            Self::Dactylic => "Дактилическая",
+            Self::Hyperdactylic => "Гипердактилическая",
+        }
+    }
+
+    pub fn badge(self) -> &'static str {
+        match self {
+            Self::Masculine => "М",
+            Self::Feminine => "Ж",
+            Self::Dactylic => "Д",
+            Self::Hyperdactylic => "Г",
+        }
+    }
+}
+
+/// How many first lines the meter is detected from.
+const DETECT_LINES: usize = 4;
+
+/// Classic syllabo-tonic meters.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy)]
+pub enum Meter {
+    Iamb,
+    Trochee,
+    Dactyl,
+    Amphibrach,
+    Anapest,
+}
+
+impl Meter {
+    pub const ALL: [Self; 5] = [
+        Self::Iamb,
+        Self::Trochee,
+        Self::Dactyl,
+        Self::Amphibrach,
+        Self::Anapest,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Iamb => "Ямб",
+            Self::Trochee => "Хорей",
+            Self::Dactyl => "Дактиль",
+            Self::Amphibrach => "Амфибрахий",
+            Self::Anapest => "Анапест",
+        }
+    }
+
+    /// Syllables in a foot and the index of the strong one.
+    fn foot(self) -> (usize, usize) {
+        match self {
+            Self::Iamb => (2, 1),
+            Self::Trochee => (2, 0),
+            Self::Dactyl => (3, 0),
+            Self::Amphibrach => (3, 1),
+            Self::Anapest => (3, 2),
+        }
+    }
+
+    /// Stressed syllables of the pattern (see [`Highlighter::stress_pattern`])
+    /// falling on weak places. Skipped stresses are common and not counted.
+    ///
+    /// [`Highlighter::stress_pattern`]: super::highlighter::Highlighter::stress_pattern
+    pub fn breaks(self, pattern: &str) -> usize {
+        let (len, strong) = self.foot();
+        pattern
+            .chars()
+            .filter(|&c| c != ' ')
+            .enumerate()
+            .filter(|&(i, c)| c == '1' && i % len != strong)
+            .count()
+    }
+
+    /// The meter the first lines fit best, if there are any.
+    pub fn detect<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Option<Self> {
+        let patterns: Vec<_> = patterns
+            .into_iter()
+            .filter(|p| !p.is_empty())
+            .take(DETECT_LINES)
+            .collect();
+        if patterns.is_empty() {
+            return None;
+        }
+        Self::ALL
+            .into_iter()
+            .min_by_key(|m| patterns.iter().map(|p| m.breaks(p)).sum::<usize>())
+    }
+}
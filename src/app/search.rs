@@ -0,0 +1,729 @@
+//! Rhyme search, run off the UI thread so that the interface stays responsive.
+
+use std::{
+    cmp::Ordering,
+    collections::{
+        hash_map::{DefaultHasher, Entry},
+        HashMap,
+    },
+    hash::{Hash, Hasher},
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+};
+
+use clru::CLruCache;
+use lazy_static::lazy_static;
+use poll_promise::Promise;
+use quickpoeter::{
+    api::{find, string2word, RhymeResult},
+    finder::Word,
+    meaner::MeanTheme,
+    reader::GeneralSettings,
+};
+
+use super::{
+    error::SearchError, homophones, inflection, prosody::syllable_count, Theme, WORD_COLLECTOR,
+};
+
+/// No Russian word is longer, anything above is a paste gone wrong.
+const MAX_QUERY_LEN: usize = 40;
+
+/// Weighted parts of the score, named as their weight sliders.
+pub const SCORE_PARTS: [&str; 5] = [
+    "Тематика",
+    "Популярность слова",
+    "Ударения",
+    "Структура",
+    "Аллитерации",
+];
+
+/// How many searches are remembered.
+const FIND_CACHE_SIZE: usize = 32;
+
+lazy_static! {
+    /// Results of the recent searches by [`SearchQuery::cache_key`], with their count before the cut.
+    static ref FOUND: Mutex<CLruCache<u64, FoundEntry>> =
+        Mutex::new(CLruCache::new(NonZeroUsize::new(FIND_CACHE_SIZE).expect("non-zero")));
+    /// The theme built last, by the theme and the custom words it was built from.
+    static ref LAST_THEME: Mutex<Option<ThemeEntry>> = Mutex::new(None);
+}
+
+type FoundEntry = (Vec<(&'static Word, f32)>, usize);
+type ThemeEntry = ((Theme, String), Option<Arc<MeanTheme>>);
+
+/// Words shown as the closest to a theme.
+const THEME_NEIGHBOURS: u32 = 30;
+
+/// Rhymes in the live preview of the settings.
+const PREVIEW_COUNT: u32 = 5;
+
+/// How many times more rhymes are searched without a part of the score, so
+/// that the shown ones are still among them.
+const SCORE_PARTS_MARGIN: u32 = 4;
+
+/// Everything that affects the search results.
+#[derive(PartialEq, Clone)]
+pub struct SearchQuery {
+    pub word: String,
+    /// `word` as the core should see it, with the stress of a custom word.
+    pub core_word: String,
+    pub settings: GeneralSettings,
+    pub theme: Theme,
+    pub custom_theme_text: String,
+    pub remove_parts_of_speech: Vec<&'static str>,
+    pub show_rhymes: u32,
+    /// Candidates asked from the core, the filters leave `show_rhymes` of them at most.
+    pub search_rhymes: u32,
+    pub show_homophones: bool,
+    pub loose_pass: bool,
+    /// Minimal and maximal syllable count of the rhymes, 0 as the maximum means no limit.
+    pub syllables: (usize, usize),
+    /// Words to drop from the results, along with their forms.
+    pub excluded_words: Vec<String>,
+    /// Find out what the scores are made of, see [`SearchQuery::score_parts`].
+    pub score_parts: bool,
+    /// Drop the forms of the word and the words of the same root.
+    pub exclude_same_root: bool,
+    /// Show the spellings with е and ё as one rhyme, see [`merge_yo`].
+    pub merge_yo: bool,
+}
+
+/// A found rhyme with its score, the lower the better. The word is borrowed
+/// from the dictionary, which lives as long as the app.
+pub type Rhyme = RhymeResult<'static>;
+
+/// Words of the found rhymes, or why there are none.
+pub type FoundWords = Result<Vec<String>, SearchError>;
+
+/// Just the words of the found rhymes.
+pub fn words(found: Result<Vec<Rhyme>, SearchError>) -> FoundWords {
+    found.map(|rhymes| rhymes.into_iter().map(|r| r.word.src.clone()).collect())
+}
+
+pub struct SearchOutcome {
+    pub rhymes: Result<Vec<Rhyme>, SearchError>,
+    pub homophones: Vec<String>,
+    /// Results of the loose pass, filling up a short strict list.
+    pub loose: Vec<String>,
+    /// The dictionary couldn't analyze the query word.
+    pub unknown_query: bool,
+    /// How many rhymes matched, more than shown if they were cut.
+    pub total: usize,
+    pub score_parts: HashMap<String, [f32; 5]>,
+}
+
+impl SearchQuery {
+    fn check(&self) -> Result<(), SearchError> {
+        let word = rhymed_word(&self.word);
+        if word.chars().count() > MAX_QUERY_LEN {
+            return Err(SearchError::QueryTooLong(MAX_QUERY_LEN));
+        }
+        if !word.is_empty() && !word.chars().any(is_cyrillic) {
+            return Err(SearchError::NotCyrillic);
+        }
+        Ok(())
+    }
+
+    /// Rhymes ranked with `settings` instead of the query's own ones.
+    pub fn find(&self, settings: &GeneralSettings) -> Result<Vec<Rhyme>, SearchError> {
+        self.find_counted(settings).map(|(rhymes, _)| rhymes)
+    }
+
+    /// [`Self::find`] along with how many rhymes matched before cutting them to
+    /// [`Self::show_rhymes`].
+    pub fn find_counted(
+        &self,
+        settings: &GeneralSettings,
+    ) -> Result<(Vec<Rhyme>, usize), SearchError> {
+        let key = self.cache_key(settings);
+        if let Some((found, total)) = FOUND.lock().ok().and_then(|mut f| f.get(&key).cloned()) {
+            let rhymes = found
+                .into_iter()
+                .map(|(word, score)| Rhyme { word, score })
+                .collect();
+            return Ok((rhymes, total));
+        }
+
+        let (found, total) = self.find_uncached(settings)?;
+        if let Ok(mut cache) = FOUND.lock() {
+            let found = found.iter().map(|r| (r.word, r.score)).collect();
+            cache.put(key, (found, total));
+        }
+        Ok((found, total))
+    }
+
+    /// Everything [`Self::find`] depends on; the settings have floats, so it's
+    /// their serialization that gets hashed.
+    fn cache_key(&self, settings: &GeneralSettings) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.core_word.trim().to_lowercase().hash(&mut hasher);
+        serde_json::to_string(settings)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+        self.theme.hash(&mut hasher);
+        self.custom_theme_text.hash(&mut hasher);
+        self.remove_parts_of_speech.hash(&mut hasher);
+        self.show_rhymes.hash(&mut hasher);
+        self.search_rhymes.hash(&mut hasher);
+        self.syllables.hash(&mut hasher);
+        self.excluded_words.hash(&mut hasher);
+        self.exclude_same_root.hash(&mut hasher);
+        self.merge_yo.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn find_uncached(
+        &self,
+        settings: &GeneralSettings,
+    ) -> Result<(Vec<Rhyme>, usize), SearchError> {
+        let theme = self.mean_theme()?;
+        self.find_with_counted(settings, theme.as_deref())
+    }
+
+    fn mean_theme(&self) -> Result<Option<Arc<MeanTheme>>, SearchError> {
+        mean_theme(&self.theme, &self.custom_theme_text)
+    }
+
+    pub fn find_with(
+        &self,
+        settings: &GeneralSettings,
+        theme: Option<&MeanTheme>,
+    ) -> Result<Vec<Rhyme>, SearchError> {
+        self.find_with_counted(settings, theme)
+            .map(|(rhymes, _)| rhymes)
+    }
+
+    fn find_with_counted(
+        &self,
+        settings: &GeneralSettings,
+        theme: Option<&MeanTheme>,
+    ) -> Result<(Vec<Rhyme>, usize), SearchError> {
+        self.check()?;
+        let excluded = self.excluded_forms();
+        let word = rhymed_word(&self.word);
+        // goes through the whole dictionary, not worth it unless needed
+        let forms = match self.exclude_same_root {
+            true => inflection::forms(&WORD_COLLECTOR, &word.to_lowercase()),
+            false => vec![],
+        };
+        let same_root = |w: &String| forms.contains(w) || inflection::same_root(word, w);
+        let query = string2word(&WORD_COLLECTOR, &self.core_word)
+            .map_err(|_| SearchError::UnknownQueryWord(word.to_string()))?;
+        find(
+            &WORD_COLLECTOR,
+            settings,
+            query,
+            theme,
+            &self.remove_parts_of_speech,
+            self.search_rhymes.max(self.show_rhymes),
+        )
+        .map(|r| {
+            let mut rhymes: Vec<_> = r
+                .into_iter()
+                .filter(|r| self.syllables_fit(syllable_count(&r.word.src)))
+                .filter(|r| !excluded.contains(&r.word.src))
+                .filter(|r| !self.exclude_same_root || !same_root(&r.word.src))
+                .collect();
+            rhymes.sort_by(rank);
+            if self.merge_yo {
+                rhymes = merge_yo(rhymes);
+            }
+            let total = rhymes.len();
+            rhymes.truncate(self.show_rhymes as usize);
+            (rhymes, total)
+        })
+        .map_err(SearchError::Core)
+    }
+
+    fn excluded_forms(&self) -> Vec<String> {
+        let mut forms = vec![];
+        for word in &self.excluded_words {
+            forms.extend(inflection::forms(&WORD_COLLECTOR, word));
+            forms.push(word.clone());
+        }
+        forms
+    }
+
+    fn syllables_fit(&self, count: usize) -> bool {
+        syllables_fit(self.syllables, count)
+    }
+
+    pub fn run(&self) -> SearchOutcome {
+        let (mut rhymes, total) = match self.find_counted(&self.settings) {
+            Ok((rhymes, total)) => (Ok(rhymes), total),
+            Err(e) => (Err(e), 0),
+        };
+        let unknown_query = matches!(rhymes, Err(SearchError::UnknownQueryWord(_)));
+
+        let homophones = if self.show_homophones && rhymes.is_ok() {
+            homophones::find_homophones(
+                &WORD_COLLECTOR,
+                &self.settings,
+                rhymed_word(&self.word),
+                &self.remove_parts_of_speech,
+                self.show_rhymes,
+            )
+            .unwrap_or_default()
+        } else {
+            vec![]
+        };
+
+        if let Ok(res) = &mut rhymes {
+            res.retain(|r| !homophones.contains(&r.word.src));
+        }
+
+        let loose = match &rhymes {
+            Ok(res) if self.loose_pass && res.len() < self.show_rhymes as usize => {
+                let missing = self.show_rhymes as usize - res.len();
+                words(self.find(&loosened(&self.settings)))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|w| !res.iter().any(|r| r.word.src == *w) && !homophones.contains(w))
+                    .take(missing)
+                    .collect()
+            }
+            _ => vec![],
+        };
+
+        let score_parts = match &rhymes {
+            Ok(res) if self.score_parts => self.score_parts(res),
+            _ => HashMap::new(),
+        };
+
+        SearchOutcome {
+            rhymes,
+            homophones,
+            loose,
+            unknown_query,
+            total,
+            score_parts,
+        }
+    }
+
+    /// How much each part of [`SCORE_PARTS`] adds to the scores of the rhymes,
+    /// taking the score to be the sum of the weighted parts. The core reports
+    /// only the sum, so it's the drop of the score with the part's weight
+    /// zeroed, a search per part; NaN if the rhyme isn't found without it.
+    fn score_parts(&self, rhymes: &[Rhyme]) -> HashMap<String, [f32; 5]> {
+        let mut parts: HashMap<String, [f32; 5]> = rhymes
+            .iter()
+            .map(|r| (r.word.src.clone(), [0.0; 5]))
+            .collect();
+        let query = SearchQuery {
+            show_rhymes: self.show_rhymes * SCORE_PARTS_MARGIN,
+            search_rhymes: self.search_rhymes * SCORE_PARTS_MARGIN,
+            ..self.clone()
+        };
+
+        for i in 0..SCORE_PARTS.len() {
+            let mut settings = self.settings.clone();
+            let weight = match i {
+                0 => &mut settings.meaning.weight,
+                1 => &mut settings.popularity.weight,
+                2 => &mut settings.stresses.weight,
+                3 => &mut settings.consonant_structure.weight,
+                _ => &mut settings.alliteration.weight,
+            };
+            if *weight == 0.0 {
+                continue;
+            }
+            *weight = 0.0;
+
+            let without: HashMap<_, _> = query
+                .find(&settings)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|r| (&r.word.src, r.score))
+                .collect();
+            for r in rhymes {
+                let part = &mut parts.get_mut(&r.word.src).expect("filled above")[i];
+                *part = without.get(&r.word.src).map_or(f32::NAN, |s| r.score - s);
+            }
+        }
+        parts
+    }
+}
+
+/// Order of the results: by score, equal ones alphabetically and then the more
+/// popular first, so that they don't shuffle between runs.
+fn rank(a: &Rhyme, b: &Rhyme) -> Ordering {
+    a.score
+        .total_cmp(&b.score)
+        .then_with(|| a.word.src.cmp(&b.word.src))
+        .then_with(|| b.word.popularity.total_cmp(&a.word.popularity))
+}
+
+/// Collapses the spellings of a word with е instead of ё into one rhyme, at the
+/// place of the better scored one but spelled with ё.
+fn merge_yo(rhymes: Vec<Rhyme>) -> Vec<Rhyme> {
+    let mut merged: Vec<Rhyme> = vec![];
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for rhyme in rhymes {
+        match index.entry(rhyme.word.src.replace('ё', "е")) {
+            Entry::Occupied(i) => {
+                if rhyme.word.src.contains('ё') {
+                    merged[*i.get()].word = rhyme.word;
+                }
+            }
+            Entry::Vacant(i) => {
+                i.insert(merged.len());
+                merged.push(rhyme);
+            }
+        }
+    }
+    merged
+}
+
+/// Rhymes to the last words of the lines, cached by the word.
+#[derive(Default)]
+pub struct LineRhymes {
+    /// The query the cached rhymes were found with, without the word.
+    base: Option<SearchQuery>,
+    found: HashMap<String, Result<Vec<String>, SearchError>>,
+    running: HashMap<String, Promise<Result<Vec<String>, SearchError>>>,
+}
+
+impl LineRhymes {
+    /// Starts searching the rhymes in the background unless they are already
+    /// known or being found for the word and settings.
+    pub fn find(&mut self, query: &SearchQuery) {
+        let mut base = query.clone();
+        base.word.clear();
+        base.core_word.clear();
+        if self.base.as_ref() != Some(&base) {
+            self.found.clear();
+            self.running.clear();
+            self.base = Some(base);
+        }
+        let word = &query.core_word;
+        if !self.found.contains_key(word) && !self.running.contains_key(word) {
+            let query = query.clone();
+            let promise = spawn("line rhymes", move || words(query.find(&query.settings)));
+            self.running.insert(word.clone(), promise);
+        }
+    }
+
+    /// Picks up the rhymes found since the last call.
+    pub fn poll(&mut self) {
+        for (word, promise) in std::mem::take(&mut self.running) {
+            match promise.try_take() {
+                Ok(found) => {
+                    self.found.insert(word, found);
+                }
+                Err(unfinished) => {
+                    self.running.insert(word, unfinished);
+                }
+            }
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.running.is_empty()
+    }
+
+    pub fn get(&self, core_word: &str) -> Option<&Result<Vec<String>, SearchError>> {
+        self.found.get(core_word)
+    }
+}
+
+fn is_cyrillic(c: char) -> bool {
+    ('\u{400}'..='\u{4ff}').contains(&c)
+}
+
+/// Part of the query the rhymes are matched with: the last word of a phrase.
+pub fn rhymed_word(query: &str) -> &str {
+    query.split_whitespace().last().unwrap_or("")
+}
+
+/// Settings for the loose pass: stress and sound mismatches are punished less.
+fn loosened(settings: &GeneralSettings) -> GeneralSettings {
+    let mut loose = settings.clone();
+    loose.stresses.k_strict_stress *= 0.5;
+    loose.stresses.k_not_strict_stress *= 0.5;
+    loose.stresses.bad_rythm *= 0.25;
+    loose.consonant_structure.weight *= 0.5;
+    loose.alliteration.weight *= 0.5;
+    loose
+}
+
+/// Settings for exact rhymes only: stresses have to match and the rythm has to hold,
+/// at the top of their sliders.
+pub fn strict(settings: &GeneralSettings) -> GeneralSettings {
+    let mut strict = settings.clone();
+    strict.stresses.k_strict_stress = 40.0;
+    strict.stresses.k_not_strict_stress = 7.0;
+    strict.stresses.bad_rythm = 100.0;
+    strict
+}
+
+/// Building the theme goes through the dictionary, so the last one is
+/// reused until the theme or its custom words change.
+fn mean_theme(
+    theme: &Theme,
+    custom_theme_text: &str,
+) -> Result<Option<Arc<MeanTheme>>, SearchError> {
+    let key = (theme.clone(), custom_theme_text.to_string());
+    let last = LAST_THEME.lock().ok().and_then(|last| last.clone());
+    if let Some((_, built)) = last.filter(|(k, _)| *k == key) {
+        return Ok(built);
+    }
+
+    let built = theme
+        .mean_theme(custom_theme_text)
+        .map_err(|err| match err.len() {
+            0 => SearchError::EmptyTheme,
+            _ => SearchError::UnknownThemeWords(err),
+        })?
+        .map(Arc::new);
+    if let Ok(mut last) = LAST_THEME.lock() {
+        *last = Some((key, built.clone()));
+    }
+    Ok(built)
+}
+
+/// Words the theme is associated with: ranked by meaning alone, from the
+/// first word of the theme, which is needed to rank anything at all.
+pub fn theme_neighbours(
+    settings: &GeneralSettings,
+    theme: &Theme,
+    custom_theme_text: &str,
+) -> FoundWords {
+    let built = mean_theme(theme, custom_theme_text)?.ok_or(SearchError::EmptyTheme)?;
+    let words = theme.words(custom_theme_text);
+    let first = words.first().ok_or(SearchError::EmptyTheme)?;
+
+    let mut settings = settings.clone();
+    settings.popularity.weight = 0.0;
+    settings.stresses.weight = 0.0;
+    settings.consonant_structure.weight = 0.0;
+    settings.alliteration.weight = 0.0;
+
+    string2word(&WORD_COLLECTOR, first)
+        .and_then(|word| {
+            find(
+                &WORD_COLLECTOR,
+                &settings,
+                word,
+                Some(&built),
+                &[],
+                THEME_NEIGHBOURS + words.len() as u32,
+            )
+        })
+        .map(|found| {
+            found
+                .into_iter()
+                .map(|r| r.word.src.clone())
+                .filter(|w| !words.contains(w))
+                .take(THEME_NEIGHBOURS as usize)
+                .collect()
+        })
+        .map_err(SearchError::Core)
+}
+
+/// Whether `count` syllables are within the limits, 0 as the maximum meaning no limit.
+pub fn syllables_fit((min, max): (usize, usize), count: usize) -> bool {
+    count >= min && (max == 0 || count <= max)
+}
+
+/// Settings from exact rhymes only at `tolerance` 0 to the loosest at 1: the
+/// penalties for mismatched sounds go from the top of their sliders down.
+pub fn tolerant(settings: &GeneralSettings, tolerance: f32) -> GeneralSettings {
+    let lerp = |strict: f32, loose: f32| strict + (loose - strict) * tolerance;
+    let mut tolerant = settings.clone();
+    tolerant.stresses.k_strict_stress = lerp(40.0, 5.0);
+    tolerant.stresses.k_not_strict_stress = lerp(7.0, 1.0);
+    tolerant.stresses.bad_rythm = lerp(100.0, 10.0);
+    tolerant.consonant_structure.weight = lerp(10.0, 2.0);
+    tolerant.alliteration.weight = lerp(10.0, 2.0);
+    tolerant
+}
+
+/// Which part of the words has to sound alike.
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Clone, Copy, Default)]
+pub enum RhymeMode {
+    /// Everything after the stressed vowel, as the default settings have it.
+    #[default]
+    End,
+    /// Mostly the stressed syllable, the unstressed tail matters little.
+    Stressed,
+}
+
+impl RhymeMode {
+    pub const ALL: [Self; 2] = [Self::End, Self::Stressed];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::End => "Рифма до конца",
+            Self::Stressed => "Рифма по ударному слогу",
+        }
+    }
+
+    /// Settings to search with in the mode: the user's ones as they are for
+    /// [`Self::End`], with the vowels of the tail weighted less for [`Self::Stressed`].
+    pub fn apply(self, settings: &GeneralSettings) -> GeneralSettings {
+        let mut applied = settings.clone();
+        if self == Self::Stressed {
+            // only the vowels near the stress count, short rhymes are fine
+            applied.stresses.shift_syll_ending *= 0.3;
+            applied.stresses.pow_syll_ending *= 2.0;
+            applied.unsymmetrical.less_w *= 0.2;
+        }
+        applied
+    }
+}
+
+/// Runs `f` in the background, in a thread named `name` where there are threads.
+#[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+pub fn spawn<T: Send + 'static>(name: &str, f: impl FnOnce() -> T + Send + 'static) -> Promise<T> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return Promise::spawn_thread(name, f);
+
+    // no threads on the web, but at least the frame with the spinner gets painted
+    #[cfg(target_arch = "wasm32")]
+    return Promise::spawn_local(async move { f() });
+}
+
+/// Takes the value out of a finished promise, leaving an unfinished one in place.
+fn take_ready<T: Send>(promise: &mut Option<Promise<T>>) -> Option<T> {
+    match promise.take()?.try_take() {
+        Ok(value) => Some(value),
+        Err(unfinished) => {
+            *promise = Some(unfinished);
+            None
+        }
+    }
+}
+
+/// Top rhymes of the last search found again with the settings being edited.
+#[derive(Default)]
+pub struct SettingsPreview {
+    /// Query of the shown or running preview.
+    query: Option<SearchQuery>,
+    running: Option<Promise<Result<Vec<String>, SearchError>>>,
+    pub words: Option<Result<Vec<String>, SearchError>>,
+}
+
+impl SettingsPreview {
+    /// Picks up the finished preview and starts a new one if the settings
+    /// changed; one at a time, so that dragging a slider doesn't pile them up.
+    pub fn update(&mut self, searched: &SearchQuery, settings: &GeneralSettings) {
+        if let Some(found) = take_ready(&mut self.running) {
+            self.words = Some(found);
+        }
+
+        let query = SearchQuery {
+            settings: settings.clone(),
+            show_rhymes: PREVIEW_COUNT,
+            ..searched.clone()
+        };
+        if self.running.is_none() && self.query.as_ref() != Some(&query) {
+            self.query = Some(query.clone());
+            self.running = Some(spawn("preview", move || words(query.find(&query.settings))));
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+}
+
+/// A search in flight.
+pub struct PendingSearch {
+    pub query: SearchQuery,
+    /// The preview replaced the displayed results.
+    pub preview_shown: bool,
+    /// Quick ranking without the theme, shown while the full one is computed.
+    preview: Option<Promise<Result<Vec<Rhyme>, SearchError>>>,
+    outcome: Option<Promise<SearchOutcome>>,
+}
+
+impl PendingSearch {
+    pub fn start(query: SearchQuery) -> Self {
+        // ranking by meaning is the slow part, worth showing the rhymes by sound meanwhile
+        let preview = (query.theme != Theme::No).then(|| {
+            let query = query.clone();
+            spawn("search", move || query.find_with(&query.settings, None))
+        });
+
+        let outcome = {
+            let query = query.clone();
+            spawn("search", move || query.run())
+        };
+
+        Self {
+            query,
+            preview_shown: false,
+            preview,
+            outcome: Some(outcome),
+        }
+    }
+
+    /// The preview, once it's ready; returned only once.
+    pub fn take_preview(&mut self) -> Option<Result<Vec<Rhyme>, SearchError>> {
+        take_ready(&mut self.preview)
+    }
+
+    /// The results, once they're ready; returned only once.
+    pub fn take_outcome(&mut self) -> Option<SearchOutcome> {
+        take_ready(&mut self.outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(word: &str) -> SearchQuery {
+        SearchQuery {
+            word: word.to_string(),
+            core_word: word.to_string(),
+            settings: GeneralSettings::default(),
+            theme: Theme::No,
+            custom_theme_text: String::new(),
+            remove_parts_of_speech: vec![],
+            show_rhymes: 10,
+            search_rhymes: 10,
+            show_homophones: false,
+            loose_pass: false,
+            syllables: (0, 0),
+            excluded_words: vec![],
+            score_parts: false,
+            exclude_same_root: false,
+            merge_yo: false,
+        }
+    }
+
+    #[test]
+    fn indexation_changes_the_cache_key() {
+        let query = query("кот");
+        let mut reanalyzed = query.settings.clone();
+        reanalyzed.stresses.indexation = !reanalyzed.stresses.indexation;
+        assert_ne!(
+            query.cache_key(&query.settings),
+            query.cache_key(&reanalyzed)
+        );
+    }
+
+    #[test]
+    fn indexation_changes_the_rhymes() {
+        let query = query("молоко");
+        let mut reanalyzed = query.settings.clone();
+        reanalyzed.stresses.indexation = !reanalyzed.stresses.indexation;
+        let scored = |settings: &GeneralSettings| {
+            let Ok(found) = query.find_with(settings, None) else {
+                panic!("the word is missing from the dictionary");
+            };
+            found
+                .into_iter()
+                .map(|r| (r.word.src.clone(), r.score))
+                .collect::<Vec<_>>()
+        };
+        assert_ne!(scored(&query.settings), scored(&reanalyzed));
+    }
+
+    #[test]
+    fn overlong_query() {
+        assert!(query(&"а".repeat(41)).check() == Err(SearchError::QueryTooLong(40)));
+        assert!(query(&"а".repeat(40)).check().is_ok());
+    }
+}
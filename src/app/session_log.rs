@@ -0,0 +1,99 @@
+//! Every search of the session with its outcome, to be exported as CSV for
+//! studying how the rhymes were picked.
+
+use super::i18n::tr;
+
+pub struct LoggedSearch {
+    /// Seconds since the Unix epoch.
+    pub time: f64,
+    pub query: String,
+    /// Name of the preset the settings matched, empty for the user's own settings.
+    pub profile: String,
+    pub top: Option<String>,
+    pub count: usize,
+}
+
+#[derive(Default)]
+pub struct SessionLog {
+    searches: Vec<LoggedSearch>,
+}
+
+impl SessionLog {
+    pub fn push(&mut self, search: LoggedSearch) {
+        self.searches.push(search);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.searches.is_empty()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = [
+            tr("Время"),
+            tr("Запрос"),
+            tr("Пресет"),
+            tr("Лучшая рифма"),
+            tr("Найдено"),
+        ]
+        .map(field)
+        .join(",");
+        csv.push('\n');
+        for search in &self.searches {
+            let row = [
+                utc_time(search.time),
+                field(&search.query),
+                field(&search.profile),
+                field(search.top.as_deref().unwrap_or_default()),
+                search.count.to_string(),
+            ];
+            csv += &row.join(",");
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Quoted if it has anything CSV treats specially.
+fn field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_secs_f64())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> f64 {
+    js_sys::Date::now() / 1000.0
+}
+
+/// `YYYY-MM-DD HH:MM:SS` in UTC, which spreadsheets read as a date.
+fn utc_time(seconds: f64) -> String {
+    let seconds = seconds as i64;
+    let (days, time) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+    // days to the civil date, after Howard Hinnant's algorithm
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
@@ -0,0 +1,36 @@
+//! Reading words aloud with the speech synthesis of the browser; there is
+//! nothing to read with on desktop.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether this build can speak at all.
+pub const AVAILABLE: bool = cfg!(target_arch = "wasm32");
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether the words can be read aloud and the user wants it.
+pub fn enabled() -> bool {
+    AVAILABLE && ENABLED.load(Ordering::Relaxed)
+}
+
+/// Says `text` in Russian, interrupting whatever is being said. The
+/// synthesizers put the stress where the acute accent is.
+#[cfg(target_arch = "wasm32")]
+pub fn say(text: &str) {
+    let Some(synthesis) = web_sys::window().and_then(|w| w.speech_synthesis().ok()) else {
+        return;
+    };
+    let Ok(utterance) = web_sys::SpeechSynthesisUtterance::new_with_text(text) else {
+        return;
+    };
+    utterance.set_lang("ru-RU");
+    synthesis.cancel();
+    synthesis.speak(&utterance);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn say(_text: &str) {}
@@ -0,0 +1,63 @@
+//! Poem forms: skeletons with rhyme-scheme placeholders to write into.
+
+use super::i18n::tr;
+
+/// The forms offered from the start, by their names and schemes.
+const DEFAULTS: [(&str, &str); 7] = [
+    ("Катрен, перекрёстная рифма", "АБАБ"),
+    ("Катрен, парная рифма", "ААББ"),
+    ("Катрен, опоясывающая рифма", "АББА"),
+    ("Сонет (французский)", "АББА АББА ВВГ ДДГ"),
+    ("Сонет (шекспировский)", "АБАБ ВГВГ ДЕДЕ ЖЖ"),
+    ("Онегинская строфа", "АБАБВВГГДЕЕДЖЖ"),
+    ("Лимерик", "ААББА"),
+];
+
+/// A form given by its rhyme scheme, e.g. `"АБАБ ВГВГ"`: one letter per line,
+/// lines with the same letter rhyme, spaces separate stanzas.
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct PoemTemplate {
+    pub name: String,
+    pub scheme: String,
+}
+
+impl PoemTemplate {
+    pub fn defaults() -> Vec<Self> {
+        DEFAULTS
+            .iter()
+            .map(|(name, scheme)| Self {
+                name: name.to_string(),
+                scheme: scheme.to_string(),
+            })
+            .collect()
+    }
+
+    /// The name to show: the default forms' names are translated, the user's own
+    /// are shown as written.
+    pub fn shown_name(&self) -> &str {
+        DEFAULTS
+            .iter()
+            .find(|(name, _)| *name == self.name)
+            .map_or(&self.name, |(name, _)| tr(name))
+    }
+
+    /// Text to insert: a placeholder line per scheme letter.
+    pub fn skeleton(&self) -> String {
+        self.scheme
+            .split_whitespace()
+            .map(|stanza| {
+                stanza
+                    .chars()
+                    .map(|letter| format!("… ({letter})"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// The scheme without stanza breaks, a letter per line.
+    pub fn letters(&self) -> String {
+        self.scheme.split_whitespace().collect()
+    }
+}